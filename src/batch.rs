@@ -0,0 +1,128 @@
+//!
+//! everything related to running many simulations at once
+//!
+use std::{fs::File, io::Write, path::Path, sync::atomic::{AtomicU32, Ordering}};
+
+use rayon::prelude::*;
+
+use crate::cli::SimArgs;
+use crate::config::SimulationConfig;
+use crate::genome::Genome;
+use crate::mixed::MixedGenome;
+use crate::output;
+use crate::simulation::{BasicSimulation, Simulation, SimulationResult};
+use crate::stats::{kaplan_meier, SpeciesStats};
+
+/// the seed of run number `run`, spread out so neighbouring runs get unrelated seeds
+pub fn derive_seed(base: u64, run: u64) -> u64 {
+    splitmix64(base ^ splitmix64(run))
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// runs `runs` independent simulations on all cores, each writing `<name><number>.txt`.
+/// the results come back in the order of the run numbers, however the runs finished
+pub fn run_parallel<G: Genome>(args: &SimArgs, config: &SimulationConfig, base_seed: u64, runs: u32) -> Vec<SimulationResult> {
+    let done = AtomicU32::new(0);
+    let results = (0..runs).into_par_iter().map(|s| {
+        let name = format!("{}{}", args.name, s);
+        let file = File::create(args.output.join(format!("{}.txt", name))).expect("file problem");
+        let seed = derive_seed(base_seed, s as u64);
+        let mut sim:BasicSimulation<G> = BasicSimulation::new(args.epochs, args.steps, args.mutation_chance, config.clone(), seed, file);
+        sim.set_continue_after_die_out(args.continue_after_die_out);
+        output::attach(&mut sim, &args.output, &name, &args.files);
+        sim.run();
+        let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+        eprint!("\rsimulations done: {}/{}", finished, runs);
+        sim.res
+    }).collect();
+    eprintln!();
+    results
+}
+
+/// runs a batch and writes the overview of every run and the totals to res.txt
+pub fn run_batch(args: &SimArgs, config: &SimulationConfig, seed: u64, num_of_simulations: u32, threads: Option<usize>) -> Vec<SimulationResult> {
+    let names: Vec<String> = config.species.iter().map(|s| s.name.clone()).collect();
+    let mut completed = 0;
+    let mut died_out = vec![0; names.len()];
+    let mut averages = vec![0; names.len()];
+    let mut res_file = File::create(args.output.join("res.txt")).expect("file problem");
+    println!("seed: {}", seed);
+    res_file.write_all(format!("Seed: {}\nEpochs: {}\n", seed, args.epochs).as_bytes()).expect("res file fail!");
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(threads.unwrap_or(0)).build().expect("could not start threads");
+    let results = pool.install(|| run_parallel::<MixedGenome>(args, config, seed, num_of_simulations));
+    for (s, res) in results.iter().enumerate(){
+        if res.epoch == args.epochs {
+            completed += 1;
+        }
+        let die_out_txt = match &res.die_out {
+            Some(name) => format!("{} died out", name),
+            None => "working".to_owned(),
+        };
+        for (i, d) in died_out.iter_mut().enumerate() {
+            if res.get_extinction(i).is_some() {
+                *d += 1;
+            }
+        }
+        let mut average_txt = String::new();
+        for (i, name) in names.iter().enumerate() {
+            let a = res.get_average(i);
+            averages[i] += a;
+            average_txt += &format!(" average {}: {}", name, a);
+        }
+        println!("Simulation number: {} -> stoped at: {} -{} -- {}", s, res.epoch, average_txt, die_out_txt);
+        res_file.write_all(format!("Simulation number: {} -> stoped at: {} -{} -- {}\n", s, res.epoch, average_txt, die_out_txt).as_bytes()).expect("res file fail!");
+
+        let extinct: Vec<String> = names.iter().enumerate()
+            .map(|(i, name)| format!("{}: {}", name, res.get_extinction(i).map_or(String::from("-"), |e| e.to_string())))
+            .collect();
+        res_file.write_all(format!("Extinct at epoch {}\n", extinct.join(" ")).as_bytes()).expect("res file fail!");
+        for (i, name) in names.iter().enumerate() {
+            let o = res.get_start_stats(i);
+            res_file.write_all(stats_format(&format!("Average {} Start", name), &o).as_bytes()).expect("res file fail!");
+        }
+        for (i, name) in names.iter().enumerate() {
+            let o = res.get_end_stats(i);
+            res_file.write_all(stats_format(&format!("Average {} End", name), &o).as_bytes()).expect("res file fail!");
+        }
+    }
+    let died_out_txt: Vec<String> = names.iter().zip(&died_out).map(|(n, d)| format!("{} died out: {} times", n, d)).collect();
+    let averages_txt: Vec<String> = names.iter().zip(&averages).map(|(n, a)| format!("{} average: {}", n, a/num_of_simulations as i32)).collect();
+    println!("Simulations completed: {} {}", completed, died_out_txt.join(" and "));
+    res_file.write_all(format!("Simulations completed: {} {} \n{}\n", completed, died_out_txt.join(" and "), averages_txt.join(" ")).as_bytes()).expect("res file fail!");
+    write_survival(&args.output.join("survival.csv"), &names, &results);
+    results
+}
+
+/// writes the share of runs every species survived each epoch in, estimated with kaplan-meier.
+/// a run stopped by another species dying out counts as censored
+fn write_survival(path: &Path, names: &[String], results: &[SimulationResult]) {
+    let last = results.iter().map(|r| r.epoch).max().unwrap_or(0);
+    let curves: Vec<Vec<f64>> = (0..names.len()).map(|i| {
+        let runs: Vec<(u16, bool)> = results.iter().map(|r| match r.get_extinction(i) {
+            Some(e) => (e, true),
+            None => (r.epoch, false),
+        }).collect();
+        kaplan_meier(&runs, last)
+    }).collect();
+    let mut file = File::create(path).expect("file problem");
+    let header: Vec<String> = names.iter().map(|n| format!("{}_survival", n)).collect();
+    writeln!(file, "epoch,{}", header.join(",")).expect("write went wrong");
+    for e in 0..last as usize {
+        let row: Vec<String> = curves.iter().map(|c| c[e].to_string()).collect();
+        writeln!(file, "{},{}", e + 1, row.join(",")).expect("write went wrong");
+    }
+}
+
+///the means of the genes, as in the older result files with the added genes at the end
+fn stats_format(s: &str, o: &SpeciesStats) -> String {
+    let other: String = o.other.iter().map(|(name, g)| format!(", {}: {}", name, g.mean as f32)).collect();
+    format!("{} w: {}, s: {}, p: {}, d: {}, eval- 1: {}, 2: {}, 3: {}{} \n", s,
+        o.weight.mean as f32, o.speed.mean as f32, o.power.mean as f32, o.detection.mean as f32,
+        o.eval_1.mean as f32, o.eval_2.mean as f32, o.eval_3.mean as f32, other)
+}
@@ -0,0 +1,166 @@
+//!
+//! everything related to the command line
+//!
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::output::DataFormat;
+
+/// evolution simulation of herbivores and carnivores
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// runs a single simulation
+    Run(SimArgs),
+    /// runs a single simulation in a terminal ui with pause, single steps and a cursor
+    /// to inspect the creatures
+    Watch(SimArgs),
+    /// runs a sequenz of simulations in parallel and writes a result file
+    Batch {
+        #[command(flatten)]
+        sim: SimArgs,
+        /// number of simulations
+        #[arg(short, long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+        runs: u32,
+        /// number of simulations running at the same time, all cores if missing
+        #[arg(short, long)]
+        threads: Option<usize>,
+    },
+    /// runs a batch for every combination of the parameters in an experiment file
+    /// and writes a table of the results to sweep.csv
+    Sweep {
+        /// .toml file with the replicates and the swept parameters
+        experiment: PathBuf,
+        #[command(flatten)]
+        sim: SimArgs,
+        /// number of simulations running at the same time, all cores if missing
+        #[arg(short, long)]
+        threads: Option<usize>,
+    },
+    /// continues a simulation from a checkpoint written with --checkpoints
+    Resume {
+        /// <name>.epoch<n>.checkpoint.json
+        checkpoint: PathBuf,
+        /// directory for all written files, gets created if missing
+        #[arg(short, long, default_value = "sim_data/output")]
+        output: PathBuf,
+        /// name of the log file
+        #[arg(short, long, default_value = "resumed")]
+        name: String,
+        /// last epoch, the one of the saved run if missing
+        #[arg(short, long)]
+        epochs: Option<u16>,
+        #[command(flatten)]
+        files: OutputArgs,
+    },
+    /// draws a recorded run, backward if --to comes before --from
+    Replay {
+        /// <name>.events.jsonl written with --events
+        file: PathBuf,
+        /// first frame as `epoch` or `epoch:step`, step 0 is the start of the epoch
+        #[arg(long, value_parser = parse_frame)]
+        from: Option<(u16, u16)>,
+        /// last frame as `epoch` or `epoch:step`
+        #[arg(long, value_parser = parse_frame)]
+        to: Option<(u16, u16)>,
+        /// frames per second
+        #[arg(long, default_value_t = 1.0)]
+        fps: f32,
+    },
+    /// prints the founders with living descendants, with the number of descendants in brackets
+    Lineage {
+        /// <name>.lineages.csv written with --data csv
+        file: PathBuf,
+    },
+    /// prints the totals of an existing batch result file
+    Summarize {
+        /// result file written by `batch`
+        file: PathBuf,
+    },
+    /// compares batch result files against the first one: die out rates, survival over the epochs and gene means
+    Compare {
+        /// result files written by `batch`
+        #[arg(required = true, num_args = 2..)]
+        files: Vec<PathBuf>,
+        /// print the survival every that many epochs
+        #[arg(long, default_value_t = 5)]
+        every: u16,
+    },
+    /// draws svg charts of a run written with --data and of batch result files
+    Plot {
+        /// `<output>/<name>` of a run written with --data, gives <name>.counts.svg and <name>.genes.svg
+        #[arg(long, required_unless_present = "batch")]
+        run: Option<PathBuf>,
+        /// result file written by `batch`, gives <file>.dieout.svg
+        #[arg(long)]
+        batch: Option<PathBuf>,
+        /// folder for the charts, the folder of the input by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// options shared by every command that runs simulations
+#[derive(Args, Debug, Clone)]
+pub struct SimArgs {
+    /// .toml or .json file with the simulation parameters
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+    /// directory for all written files, gets created if missing
+    #[arg(short, long, default_value = "sim_data/output")]
+    pub output: PathBuf,
+    /// name of the log file (batch runs append the simulation number)
+    #[arg(short, long, default_value = "test")]
+    pub name: String,
+    #[arg(short, long, default_value_t = 40, value_parser = clap::value_parser!(u16).range(1..))]
+    pub epochs: u16,
+    /// simulation steps per epoch
+    #[arg(short, long, default_value_t = 30, value_parser = clap::value_parser!(u16).range(1..))]
+    pub steps: u16,
+    /// chance value of 1 equals a mutation chance of 0.1%
+    #[arg(short, long, default_value_t = 150)]
+    pub mutation_chance: i32,
+    /// seed for the random number generator, a random one is chosen and printed if missing.
+    /// batch runs derive the seed of every simulation from it
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// keeps simulating the other species after one died out, until all are gone
+    #[arg(long)]
+    pub continue_after_die_out: bool,
+    #[command(flatten)]
+    pub files: OutputArgs,
+}
+
+/// files a simulation writes next to its log
+#[derive(Args, Debug, Clone)]
+pub struct OutputArgs {
+    /// also writes typed step, epoch and genome records in this format next to the log
+    #[arg(long, value_enum)]
+    pub data: Option<DataFormat>,
+    /// records every change of the world to <name>.events.jsonl for `replay`
+    #[arg(long)]
+    pub events: bool,
+    /// epochs before the last one after which the whole state is saved, e.g. `10,20`
+    #[arg(long, value_delimiter = ',')]
+    pub checkpoints: Vec<u16>,
+    /// draws every step to <name>.frames/e<epoch>_s<step>.png
+    #[arg(long)]
+    pub png: bool,
+    /// draws every epoch as an animation to <name>.epoch<n>.gif
+    #[arg(long)]
+    pub gif: bool,
+}
+
+/// reads `epoch` or `epoch:step`
+fn parse_frame(text: &str) -> Result<(u16, u16), String> {
+    let (epoch, step) = text.split_once(':').unwrap_or((text, "0"));
+    let number = |n: &str| n.parse::<u16>().map_err(|e| format!("{}: {}", n, e));
+    Ok((number(epoch)?, number(step)?))
+}
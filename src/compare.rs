@@ -0,0 +1,167 @@
+//!
+//! everything related to comparing the results of several batches
+//!
+use std::{collections::BTreeMap, path::Path};
+
+use crate::data::{batch_species, read_batch, same_species, BatchRun};
+use crate::genome::GENES;
+use crate::stats::{chi_square, cohen_d, kaplan_meier, mean_sd, newcombe, wilson};
+
+/// 95% of the normal distribution
+const Z: f64 = 1.959964;
+
+/// a batch result file with the name it is shown with
+struct Batch {
+    name: String,
+    runs: Vec<BatchRun>,
+}
+
+/// compares batch result files against the first one: the die out rates with wilson
+/// intervals, their differences and a chi-square test, the kaplan-meier survival of every
+/// species printed every `every` epochs and the effect sizes of the gene means at the end
+pub fn compare(files: &[impl AsRef<Path>], every: u16) {
+    let paths: Vec<&Path> = files.iter().map(AsRef::as_ref).collect();
+    let batches: Vec<Batch> = paths.iter().zip(labels(&paths)).map(|(f, name)| {
+        Batch { name, runs: read_batch(f).expect("could not read batch") }
+    }).collect();
+    let species = batch_species(batches.iter().flat_map(|b| b.runs.iter()));
+    let reference = &batches[0];
+    let overview: Vec<String> = batches.iter().map(|b| format!("{} ({} runs)", b.name, b.runs.len())).collect();
+    println!("comparing {} against {}", overview.join(", "), reference.name);
+    let width = batches.iter().map(|b| b.name.len()).max().unwrap_or(0).max(6);
+
+    println!();
+    println!("die out rate with 95% wilson interval, difference to {} with newcombe interval and chi-square p", reference.name);
+    for name in &species {
+        println!("{}", name);
+        //files whose runs never name the species are left out of the tests
+        let groups: Vec<Option<(usize, usize)>> = batches.iter().map(|b| {
+            b.runs.iter().any(|r| r.has(name)).then(|| (b.runs.iter().filter(|r| died(r, name)).count(), b.runs.len()))
+        }).collect();
+        for (i, b) in batches.iter().enumerate() {
+            let Some((k, n)) = groups[i] else {
+                println!("  {:<width$} -", b.name);
+                continue;
+            };
+            let (lo, hi) = wilson(k, n, Z);
+            let mut line = format!("  {:<width$} {:>4}/{:<4} = {:.3} [{:.3}, {:.3}]", b.name, k, n, k as f64 / n as f64, lo, hi);
+            if let (true, Some((r, rn))) = (i > 0, groups[0]) {
+                let (lo, hi) = newcombe(k, n, r, rn, Z);
+                line += &format!(" diff {:+.3} [{:+.3}, {:+.3}] p {}", k as f64 / n as f64 - r as f64 / rn as f64, lo, hi, p_value(&[(r, rn), (k, n)]));
+            }
+            println!("{}", line);
+        }
+        if batches.len() > 2 {
+            let present: Vec<(usize, usize)> = groups.iter().flatten().copied().collect();
+            match chi_square(&present) {
+                Some((x, df, p)) => println!("  all files: chi-square {:.3} df {} p {:.4}", x, df, p),
+                None => println!("  all files: no chi-square, the species died out in every run or in none or lives in fewer than two files"),
+            }
+        }
+    }
+
+    let last = batches.iter().flat_map(|b| b.runs.iter().map(|r| r.stopped)).max().unwrap_or(0);
+    for name in &species {
+        println!();
+        println!("survival of {} (kaplan-meier)", name);
+        let curves: Vec<Option<Vec<f64>>> = batches.iter().map(|b| {
+            let runs: Vec<(u16, bool)> = b.runs.iter().filter(|r| r.has(name)).map(|r| match r.extinction(name) {
+                Some(e) => (e, true),
+                None => (r.stopped, false),
+            }).collect();
+            (!runs.is_empty()).then(|| kaplan_meier(&runs, last))
+        }).collect();
+        let header: Vec<String> = batches.iter().map(|b| format!("{:>width$}", b.name)).collect();
+        println!("  epoch {}", header.join(" "));
+        for epoch in (1..=last).filter(|e| e % every.max(1) == 0 || *e == last) {
+            let row: Vec<String> = curves.iter().map(|c| match c {
+                Some(c) => format!("{:>width$.3}", c[epoch as usize - 1]),
+                None => format!("{:>width$}", "-"),
+            }).collect();
+            println!("  {:>5} {}", epoch, row.join(" "));
+        }
+        let medians: Vec<String> = batches.iter().zip(&curves).map(|(b, c)| match c.as_ref().map(|c| c.iter().position(|s| *s <= 0.5)) {
+            Some(Some(e)) => format!("{} {}", b.name, e + 1),
+            Some(None) => format!("{} >{}", b.name, last),
+            None => format!("{} -", b.name),
+        }).collect();
+        println!("  median survival: {}", medians.join(", "));
+    }
+
+    for name in &species {
+        println!();
+        println!("gene means of {} at the start of the last epoch, mean ± sd over the runs and cohen's d against {}", name, reference.name);
+        let means: Vec<Vec<&BTreeMap<String, f64>>> = batches.iter().map(|b| {
+            b.runs.iter().filter_map(|r| r.end.iter().find(|(n, _)| same_species(n, name)).map(|e| &e.1)).collect()
+        }).collect();
+        //the built in genes first, then every gene added in the config of any file
+        let mut genes: Vec<&str> = GENES.to_vec();
+        for gene in means.iter().flatten().flat_map(|m| m.keys()) {
+            if !genes.contains(&gene.as_str()) {
+                genes.push(gene);
+            }
+        }
+        for gene in genes {
+            let values: Vec<Vec<f64>> = means.iter().map(|m| m.iter().filter_map(|v| v.get(gene).copied()).collect()).collect();
+            let cells: Vec<String> = values.iter().enumerate().map(|(i, v)| {
+                if v.is_empty() {
+                    return format!("{}: -", batches[i].name);
+                }
+                let (mean, sd) = mean_sd(v);
+                let d = match (i, cohen_d(&values[0], v)) {
+                    (0, _) => String::new(),
+                    (_, Some(d)) => format!(" d {:+.2}", d),
+                    (_, None) => String::from(" d -"),
+                };
+                format!("{}: {:.3} ± {:.3}{}", batches[i].name, mean, sd, d)
+            }).collect();
+            println!("  {:<9} {}", gene, cells.join(", "));
+        }
+    }
+}
+
+/// the names the files are shown with, the shortest ending of their paths that tells them
+/// apart. `batch` always writes `res.txt`, so these are mostly the folders with the stem
+fn labels(files: &[&Path]) -> Vec<String> {
+    let parts: Vec<Vec<String>> = files.iter().map(|f| {
+        let mut parts: Vec<String> = f.with_extension("").components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+        parts.reverse();
+        parts
+    }).collect();
+    let longest = parts.iter().map(Vec::len).max().unwrap_or(0);
+    for n in 1..=longest {
+        let names: Vec<String> = parts.iter().map(|p| {
+            let mut ending: Vec<&str> = p.iter().take(n).map(String::as_str).collect();
+            ending.reverse();
+            ending.join("/")
+        }).collect();
+        if names.iter().enumerate().all(|(i, a)| names[..i].iter().all(|b| a != b)) {
+            return names;
+        }
+    }
+    files.iter().map(|f| f.display().to_string()).collect()
+}
+
+fn died(run: &BatchRun, species: &str) -> bool {
+    run.extinction(species).is_some()
+}
+
+fn p_value(groups: &[(usize, usize)]) -> String {
+    match chi_square(groups) {
+        Some((_, _, p)) => format!("{:.4}", p),
+        None => String::from("-"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_tell_the_files_apart() {
+        let labels = |files: &[&str]| labels(&files.iter().map(Path::new).collect::<Vec<&Path>>());
+        assert_eq!(labels(&["sim_data/baseline.txt", "sim_data/more_carni.txt"]), ["baseline", "more_carni"]);
+        assert_eq!(labels(&["out/a/res.txt", "out/b/res.txt"]), ["a/res", "b/res"]);
+        assert_eq!(labels(&["x/a/res.txt", "y/a/res.txt", "baseline.txt"]), ["x/a/res", "y/a/res", "baseline"]);
+    }
+}
@@ -0,0 +1,293 @@
+//!
+//! everything related to the runtime configuration
+//!
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::genome::{Crossover, Genome, Mutation};
+use crate::mixed::MixedGenome;
+use crate::neat::Neat;
+use crate::schema::{GeneConfig, Schema};
+use crate::world::{Metric, World};
+
+/// name used in a diet for the plants
+pub const PLANTS: &str = "plants";
+
+/// all parameters of a simulation run.
+/// missing keys in a config file fall back to the defaults below
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    pub plant_energy: f32,
+    pub watching: bool,
+    pub meat_efficiency: f32,
+    pub strength_contest: bool,
+    pub slow_plant_decrease: i32,
+    pub half_plant_at: u16,
+    pub world: World,
+    ///caps the detection gene, which can grow without limit through mutation
+    pub max_detection_range: Option<f32>,
+    ///shape of the detection window
+    pub perception: Metric,
+    ///the food web, every species moves in this order
+    pub species: Vec<SpeciesConfig>,
+    ///changes of the built in genes and further genes every species carries
+    pub genes: Vec<GeneConfig>,
+    pub render: RenderConfig,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            plant_energy: 1.0,
+            watching: false,
+            meat_efficiency: 2.0,
+            strength_contest: false,
+            slow_plant_decrease: 0,
+            half_plant_at: 50,
+            world: World::default(),
+            max_detection_range: None,
+            perception: Metric::Manhattan,
+            species: vec![
+                SpeciesConfig {
+                    name: String::from("Herbivore"),
+                    diet: vec![String::from(PLANTS)],
+                    predators: vec![String::from("Carnivore")],
+                    kin_eval: 2,
+                    ..SpeciesConfig::default()
+                },
+                SpeciesConfig {
+                    name: String::from("Carnivore"),
+                    diet: vec![String::from("Herbivore")],
+                    predators: vec![],
+                    kin_eval: 1,
+                    extra_mutation_chance: 150,
+                    ..SpeciesConfig::default()
+                },
+            ],
+            genes: vec![],
+            render: RenderConfig::default(),
+        }
+    }
+}
+
+/// how `--png` and `--gif` draw the world, colors are written as "#rrggbb"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderConfig {
+    ///side length of a cell in pixels
+    pub cell_size: u32,
+    ///time a gif frame is shown, in hundredths of a second
+    pub frame_delay: u16,
+    pub background: String,
+    pub plant_color: String,
+    ///colors by species name, the others get one of a fixed palette
+    pub colors: BTreeMap<String, String>,
+    pub tint: Vec<Tint>,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        RenderConfig {
+            cell_size: 4,
+            frame_delay: 10,
+            background: String::from("#101010"),
+            plant_color: String::from("#2e7d32"),
+            colors: BTreeMap::new(),
+            tint: vec![],
+        }
+    }
+}
+
+/// draws the members of a species darker the smaller one of their genes is,
+/// from a quarter of the brightness at `min` up to the full color at `max`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tint {
+    pub species: String,
+    pub gene: String,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// reads a color like "#ff8000"
+pub fn parse_color(text: &str) -> Result<[u8; 3], String> {
+    let hex = text.strip_prefix('#').filter(|h| h.len() == 6 && h.is_ascii())
+        .ok_or_else(|| format!("color {} is not of the form #rrggbb", text))?;
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("invalid color {}", text));
+    Ok([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// genome implementations a species can use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GenomeKind {
+    #[default]
+    Basic,
+    ///a small neural network chooses the direction instead of the eval weights
+    Neural,
+    ///a network whose nodes and connections evolve chooses the direction (NEAT)
+    Neat,
+}
+
+/// one population of the food web
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeciesConfig {
+    pub name: String,
+    pub genome: GenomeKind,
+    ///names of the species this one eats, "plants" for plants
+    pub diet: Vec<String>,
+    ///species this one watches out for, weighted with eval 1
+    pub predators: Vec<String>,
+    ///eval weight used for members of the own species, 0 ignores them
+    pub kin_eval: u8,
+    pub count: i32,
+    pub extra_mutation_chance: i32,
+    ///how the parents of the next generation are combined
+    pub crossover: Crossover,
+    ///how the children mutate
+    pub mutation: Mutation,
+    ///the network mutations and the speciation of the neat genome
+    pub neat: Neat,
+}
+
+impl Default for SpeciesConfig {
+    fn default() -> Self {
+        SpeciesConfig {
+            name: String::new(),
+            genome: GenomeKind::Basic,
+            diet: vec![],
+            predators: vec![],
+            kin_eval: 0,
+            count: 100,
+            extra_mutation_chance: 0,
+            crossover: Crossover::Fixed,
+            mutation: Mutation::default(),
+            neat: Neat::default(),
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// loads a config from a .toml or .json file, chosen by the file extension
+    pub fn load(path: &Path) -> Result<SimulationConfig, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read config {}: {}", path.display(), e))?;
+        let config: SimulationConfig = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&text)
+                .map_err(|e| format!("invalid json config {}: {}", path.display(), e))?,
+            _ => toml::from_str(&text)
+                .map_err(|e| format!("invalid toml config {}: {}", path.display(), e))?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// checks the world size and that the food web only references other known species
+    pub fn validate(&self) -> Result<(), String> {
+        if self.world.width < 1 || self.world.height < 1 {
+            return Err(String::from("the world needs a width and height of at least 1"));
+        }
+        if self.species.is_empty() {
+            return Err(String::from("at least one species is needed"));
+        }
+        for (i, s) in self.species.iter().enumerate() {
+            if s.name.is_empty() || s.name == PLANTS {
+                return Err(format!("species {} needs a name other than \"{}\"", i, PLANTS));
+            }
+            if self.species[..i].iter().any(|o| o.name == s.name) {
+                return Err(format!("species {} is declared twice", s.name));
+            }
+            if s.count < 2 {
+                return Err(format!("species {} needs a count of at least 2", s.name));
+            }
+            if s.kin_eval > 3 {
+                return Err(format!("kin_eval of {} has to be between 0 and 3", s.name));
+            }
+            if let Crossover::Blend(alpha) = s.crossover {
+                if !(alpha >= 0.0 && alpha.is_finite()) {
+                    return Err(format!("the blend alpha of {} has to be at least 0", s.name));
+                }
+            }
+            Self::validate_mutation(&s.name, &s.mutation, &self.schema(s)?)?;
+            Self::validate_neat(&s.name, &s.neat)?;
+            for other in s.diet.iter().chain(s.predators.iter()) {
+                if other != PLANTS && self.species_index(other).is_none() {
+                    return Err(format!("species {} references unknown species {}", s.name, other));
+                }
+                if *other == s.name {
+                    return Err(format!("species {} can not eat itself or be its own predator, its kin is weighted with kin_eval", s.name));
+                }
+            }
+        }
+        self.validate_render()
+    }
+
+    fn validate_mutation(species: &str, m: &Mutation, schema: &Schema) -> Result<(), String> {
+        if !(m.sigma > 0.0 && m.eval_sigma > 0.0 && m.tau >= 0.0) {
+            return Err(format!("the mutation of {} needs a sigma and eval_sigma above 0 and a tau of at least 0", species));
+        }
+        for gene in m.rates.keys().chain(m.bounds.keys()) {
+            if schema.index(gene).is_none() {
+                let known: Vec<&str> = schema.genes.iter().map(|g| g.name.as_str()).collect();
+                return Err(format!("the mutation of {} uses unknown gene {}, known are: {}", species, gene, known.join(", ")));
+            }
+        }
+        if let Some((gene, _)) = m.rates.iter().find(|(_, r)| !(0.0..=1.0).contains(*r)) {
+            return Err(format!("the mutation rate of {} for {} has to be between 0 and 1", species, gene));
+        }
+        for (gene, b) in &m.bounds {
+            if let (Some(min), Some(max)) = (b.min, b.max) {
+                if min > max {
+                    return Err(format!("the bounds of {} for {} need min <= max", species, gene));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_neat(species: &str, n: &Neat) -> Result<(), String> {
+        if [n.weight_rate, n.add_connection, n.add_node, n.toggle].iter().any(|r| !(0.0..=1.0).contains(r)) {
+            return Err(format!("the neat rates of {} have to be between 0 and 1", species));
+        }
+        if !(n.weight_sigma > 0.0 && n.threshold > 0.0 && n.excess >= 0.0 && n.disjoint >= 0.0 && n.weight >= 0.0) {
+            return Err(format!("the neat weight_sigma and threshold of {} have to be above 0, its coefficients at least 0", species));
+        }
+        Ok(())
+    }
+
+    fn validate_render(&self) -> Result<(), String> {
+        let render = &self.render;
+        if render.cell_size < 1 {
+            return Err(String::from("the cell_size has to be at least 1"));
+        }
+        parse_color(&render.background)?;
+        parse_color(&render.plant_color)?;
+        for (name, color) in &render.colors {
+            if self.species_index(name).is_none() {
+                return Err(format!("color for unknown species {}", name));
+            }
+            parse_color(color)?;
+        }
+        for t in &render.tint {
+            if self.species_index(&t.species).is_none() {
+                return Err(format!("tint for unknown species {}", t.species));
+            }
+            if t.min >= t.max {
+                return Err(format!("tint of {} needs a min below its max", t.species));
+            }
+        }
+        Ok(())
+    }
+
+    /// the genes the genome of `species` carries, those of the basic genome with the changes
+    /// and additions of `genes`
+    pub fn schema(&self, species: &SpeciesConfig) -> Result<Schema, String> {
+        MixedGenome::schema(&self.genes, species)
+    }
+
+    pub fn species_index(&self, name: &str) -> Option<usize> {
+        self.species.iter().position(|s| s.name == name)
+    }
+}
@@ -1,4 +1,309 @@
+//!
+//! everything related to reading written simulation data
+//!
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}};
 
-fn write_data(){
-    
-}
\ No newline at end of file
+use serde_json::Value;
+
+use crate::genealogy::{DeathCause, Genealogy};
+use crate::genome::GENES;
+
+/// recounts the totals of a batch result file like the ones in sim_data/, every species
+/// that died out counts, not only the first one of a run
+pub fn summarize(path: &Path) {
+    let runs = match read_batch(path) {
+        Ok(runs) => runs,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let species = batch_species(runs.iter());
+    //older files do not know the epochs of the batch, only runs without a die out are certain
+    let completed = match runs.iter().map(|r| r.epochs.map(|e| r.stopped == e)).collect::<Option<Vec<bool>>>() {
+        Some(completed) => format!("completed: {}", completed.iter().filter(|c| **c).count()),
+        None => format!("without a die out: {}", runs.iter().filter(|r| species.iter().all(|s| r.extinction(s).is_none())).count()),
+    };
+    let died_out: Vec<String> = species.iter()
+        .map(|s| format!("{} died out: {} times", s, runs.iter().filter(|r| r.extinction(s).is_some()).count()))
+        .collect();
+    let averages: Vec<String> = species.iter().map(|s| {
+        let sum: i32 = runs.iter().flat_map(|r| &r.averages).filter(|a| same_species(&a.0, s)).map(|a| a.1).sum();
+        format!("{} average: {}", s, sum / runs.len() as i32)
+    }).collect();
+    println!("Simulations: {} {} {}", runs.len(), completed, died_out.join(" and "));
+    println!("{}", averages.join(" "));
+}
+
+/// reads the "average <species>: <number>" pairs of a result line
+fn read_averages(line: &str) -> Vec<(String, i32)> {
+    line.split(" average ")
+        .skip(1)
+        .filter_map(|part| {
+            let (name, rest) = part.split_once(':')?;
+            let n = rest.split_whitespace().next()?.parse().ok()?;
+            Some((name.to_owned(), n))
+        })
+        .collect()
+}
+
+/// prints the founders whose descendants are still alive at the end of every run in a
+/// lineage file written with `--data csv`
+pub fn founders(path: &Path) {
+    let text = fs::read_to_string(path).expect("could not read lineage file");
+    let mut lines = text.lines();
+    let header: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+    let column = |name: &str| header.iter().position(|h| *h == name).unwrap_or_else(|| panic!("lineage file without {} column", name));
+    let (seed, species, parent_1, parent_2, born, died, cause) =
+        (column("seed"), column("species"), column("parent_1"), column("parent_2"), column("born"), column("died"), column("cause"));
+    //every run of a concatenated file gets its own family tree
+    let mut runs: Vec<(String, Vec<String>, Genealogy)> = vec![];
+    for line in lines.filter(|l| !l.is_empty()) {
+        let row: Vec<&str> = line.split(',').collect();
+        if runs.last().is_none_or(|r| r.0 != row[seed]) {
+            runs.push((row[seed].to_owned(), vec![], Genealogy::new()));
+        }
+        let (_, names, genealogy) = runs.last_mut().expect("no run");
+        let s = match names.iter().position(|n| n == row[species]) {
+            Some(s) => s,
+            None => {
+                names.push(row[species].to_owned());
+                names.len() - 1
+            }
+        };
+        let parents = match (row[parent_1].parse(), row[parent_2].parse()) {
+            (Ok(a), Ok(b)) => Some((a, b)),
+            _ => None,
+        };
+        let id = genealogy.birth(s, parents, row[born].parse().expect("invalid born epoch"));
+        if let (Ok(epoch), Some(cause)) = (row[died].parse(), DeathCause::from_name(row[cause])) {
+            genealogy.death(id, epoch, cause);
+        }
+    }
+    for (seed, names, genealogy) in runs {
+        println!("seed: {}", seed);
+        let mut founders = genealogy.surviving_founders();
+        founders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        for (s, name) in names.iter().enumerate() {
+            let list: Vec<String> = founders.iter()
+                .filter(|(id, _)| genealogy.get(*id).species == s)
+                .map(|(id, n)| format!("{} ({})", id, n))
+                .collect();
+            println!("{} founders with living descendants: {} -> {}", name, list.len(), list.join(" "));
+        }
+    }
+}
+
+/// reads the records of one kind written with `--data` for the run `<dir>/<name>`, from
+/// `<name>.<kind>s.csv` if it exists and from `<name>.jsonl` otherwise. values are kept as text
+pub fn read_records(run: &Path, kind: &str) -> Result<Vec<BTreeMap<String, String>>, String> {
+    let csv = PathBuf::from(format!("{}.{}s.csv", run.display(), kind));
+    let jsonl = PathBuf::from(format!("{}.jsonl", run.display()));
+    if csv.exists() {
+        let text = fs::read_to_string(&csv).map_err(|e| format!("could not read {}: {}", csv.display(), e))?;
+        let mut lines = text.lines();
+        let header = split_csv(lines.next().unwrap_or_default());
+        Ok(lines.filter(|l| !l.is_empty()).map(|l| header.iter().cloned().zip(split_csv(l)).collect()).collect())
+    } else if jsonl.exists() {
+        let text = fs::read_to_string(&jsonl).map_err(|e| format!("could not read {}: {}", jsonl.display(), e))?;
+        let mut records = vec![];
+        for line in text.lines().filter(|l| !l.is_empty()) {
+            let Value::Object(fields) = serde_json::from_str(line).map_err(|e| format!("invalid line in {}: {}", jsonl.display(), e))? else {
+                return Err(format!("invalid line in {}: {}", jsonl.display(), line));
+            };
+            if fields.get("record").and_then(Value::as_str) != Some(kind) {
+                continue;
+            }
+            records.push(fields.into_iter().filter(|(k, _)| k != "record").map(|(k, v)| {
+                let v = match v {
+                    Value::String(s) => s,
+                    Value::Null => String::new(),
+                    v => v.to_string(),
+                };
+                (k, v)
+            }).collect());
+        }
+        Ok(records)
+    } else {
+        Err(format!("neither {} nor {} found, write the run with --data", csv.display(), jsonl.display()))
+    }
+}
+
+/// the fields of a csv line, quoted fields may contain commas and doubled quotes
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().expect("no field").push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().expect("no field").push(c),
+        }
+    }
+    fields
+}
+
+/// one line of a batch result file with the gene means of its species
+pub struct BatchRun {
+    ///the epochs the batch was started with, older files do not write them
+    pub epochs: Option<u16>,
+    ///the epoch the run stopped at
+    pub stopped: u16,
+    pub averages: Vec<(String, i32)>,
+    ///the first species that died out
+    pub died_out: Option<String>,
+    ///the epoch every species died out at, older files only know the first one
+    pub extinct: Vec<(String, Option<u16>)>,
+    ///the means of the genes by name at the start of the last epoch, older files miss
+    ///the species that died out
+    pub end: Vec<(String, BTreeMap<String, f64>)>,
+}
+
+/// reads every run of a batch result file, new ones and the older ones in sim_data/
+pub fn read_batch(path: &Path) -> Result<Vec<BatchRun>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+    let mut runs: Vec<BatchRun> = vec![];
+    let mut epochs = None;
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("Epochs: ") {
+            epochs = Some(rest.trim().parse().map_err(|_| format!("invalid epochs: {}", line))?);
+        } else if line.starts_with("Simulation number:") {
+            let (values, state) = line.split_once(" -- ").unwrap_or((line, ""));
+            let stopped = values.split("stoped at:").nth(1)
+                .and_then(|r| r.split_whitespace().next()?.parse().ok())
+                .ok_or_else(|| format!("no epoch in: {}", line))?;
+            let died_out = state.trim().strip_suffix(" died out").map(|n| n.to_owned());
+            runs.push(BatchRun { epochs, stopped, averages: read_averages(values), died_out, extinct: vec![], end: vec![] });
+        } else if let Some(rest) = line.strip_prefix("Extinct at epoch ") {
+            let run = runs.last_mut().ok_or_else(|| format!("extinction without a run: {}", line))?;
+            for part in rest.split(' ').collect::<Vec<&str>>().chunks(2) {
+                let [name, epoch] = part else { return Err(format!("invalid extinction: {}", line)) };
+                let name = name.strip_suffix(':').ok_or_else(|| format!("invalid extinction: {}", line))?;
+                run.extinct.push((name.to_owned(), epoch.parse().ok()));
+            }
+        } else if let Some(rest) = line.strip_prefix("Average ") {
+            let Some((name, genes)) = rest.split_once(" End ") else { continue };
+            let run = runs.last_mut().ok_or_else(|| format!("gene means without a run: {}", line))?;
+            run.end.push((name.to_owned(), read_gene_means(genes).ok_or_else(|| format!("invalid gene means: {}", line))?));
+        }
+    }
+    if runs.is_empty() {
+        return Err(format!("no simulations found in {}", path.display()));
+    }
+    Ok(runs)
+}
+
+/// reads the "name: value" pairs of gene means. the built in genes come first with short
+/// names like "w" or "eval- 1", genes added in the config follow with their own names
+fn read_gene_means(genes: &str) -> Option<BTreeMap<String, f64>> {
+    let mut means = BTreeMap::new();
+    for (i, pair) in genes.split(',').enumerate() {
+        let (name, value) = pair.split_once(':')?;
+        let name = GENES.get(i).copied().unwrap_or(name.trim());
+        means.insert(name.to_owned(), value.trim().parse().ok()?);
+    }
+    (means.len() >= GENES.len()).then_some(means)
+}
+
+impl BatchRun {
+    /// every species name written for this run
+    fn names(&self) -> impl Iterator<Item = &String> {
+        self.averages.iter().map(|a| &a.0)
+            .chain(self.died_out.iter())
+            .chain(self.extinct.iter().map(|x| &x.0))
+            .chain(self.end.iter().map(|e| &e.0))
+    }
+
+    /// whether `species` lived in this run
+    pub fn has(&self, species: &str) -> bool {
+        self.names().any(|n| same_species(n, species))
+    }
+
+    /// the epoch `species` died out at in this run, if it did
+    pub fn extinction(&self, species: &str) -> Option<u16> {
+        match self.extinct.iter().find(|(n, _)| same_species(n, species)) {
+            Some((_, epoch)) => *epoch,
+            None => self.died_out.as_ref().filter(|d| same_species(d, species)).map(|_| self.stopped),
+        }
+    }
+}
+
+/// the species of batch runs, older files name them differently in different places
+/// so the longest of the names meaning the same species is used
+pub fn batch_species<'a>(runs: impl Iterator<Item = &'a BatchRun>) -> Vec<String> {
+    let mut species: Vec<String> = vec![];
+    for name in runs.flat_map(BatchRun::names) {
+        match species.iter_mut().find(|s| same_species(s, name)) {
+            Some(s) if s.len() < name.len() => *s = name.clone(),
+            Some(_) => {}
+            None => species.push(name.clone()),
+        }
+    }
+    species
+}
+
+/// whether two names mean the same species, ignoring the case. older result files shorten
+/// them to "herbi" and "carni" (or "Canri"), other names have to match exactly
+pub fn same_species(a: &str, b: &str) -> bool {
+    full_name(a) == full_name(b)
+}
+
+fn full_name(name: &str) -> String {
+    match name.to_lowercase().as_str() {
+        "herbi" => String::from("herbivore"),
+        "carni" | "canri" => String::from("carnivore"),
+        other => other.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_species_needs_the_whole_name() {
+        assert!(same_species("Carnivore", "carnivore"));
+        assert!(!same_species("Carnivore", "CarnivoreB"));
+        assert!(!same_species("Herb", "Herbivore"));
+        assert!(!same_species("Herbivore", "Herbivores"));
+    }
+
+    #[test]
+    fn same_species_knows_the_old_abbreviations() {
+        assert!(same_species("herbi", "Herbivore"));
+        assert!(same_species("Herbi", "herbivore"));
+        assert!(same_species("carni", "Carnivore"));
+        assert!(same_species("Canri", "Carnivore"));
+        assert!(!same_species("herbi", "Carnivore"));
+    }
+
+    #[test]
+    fn batches_know_their_epochs() {
+        let path = std::env::temp_dir().join("ki_hausarbeit_epochs_res.txt");
+        fs::write(&path, "Seed: 1\nEpochs: 30\nSimulation number: 0 -> stoped at: 12 - average Herbivore: 4 -- Herbivore died out\n").expect("write problem");
+        let runs = read_batch(&path).expect("invalid batch");
+        assert_eq!(runs[0].epochs, Some(30));
+        assert_eq!(runs[0].stopped, 12);
+        let old = std::env::temp_dir().join("ki_hausarbeit_old_res.txt");
+        fs::write(&old, "Simulation number: 0 -> stoped at: 40 - average herbi: 9 -- working\n").expect("write problem");
+        assert_eq!(read_batch(&old).expect("invalid batch")[0].epochs, None);
+    }
+
+    #[test]
+    fn gene_means_keep_every_gene() {
+        let old = read_gene_means("w: 1.05, s: 4.8, p: 3.1, d: 0.46, eval- 1: 171, 2: 318, 3: 0 ").expect("old means");
+        assert_eq!(old.len(), 7);
+        assert_eq!(old["speed"], 4.8);
+        assert_eq!(old["eval_2"], 318.0);
+        let new = read_gene_means("w: 1, s: 2, p: 3, d: 4, eval- 1: 5, 2: 6, 3: 7, fur: 0.5, teeth: 3 ").expect("new means");
+        assert_eq!(new.len(), 9);
+        assert_eq!(new["fur"], 0.5);
+        assert_eq!(new["teeth"], 3.0);
+        assert!(read_gene_means("w: 1, s: 2").is_none());
+    }
+}
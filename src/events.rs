@@ -0,0 +1,165 @@
+//!
+//! everything related to recording a run and playing it back
+//!
+use core::time;
+use std::{collections::HashSet, fs::{self, File}, io::{BufWriter, Write}, path::Path, thread};
+
+use serde::{Deserialize, Serialize};
+
+use crate::genealogy::DeathCause;
+use crate::simulation::{draw, letter};
+use crate::world::World;
+
+/// one change of the world. species are given by their index in the config.
+/// every event can be undone, so a replay can run backward
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Event {
+    ///a creature appears on a cell
+    #[serde(rename = "s")]
+    Spawn(usize, (i32, i32)),
+    ///a creature walks from one cell to the next
+    #[serde(rename = "m")]
+    Move(usize, (i32, i32), (i32, i32)),
+    ///hunter and prey species and the cell the prey was eaten on
+    #[serde(rename = "e")]
+    Eat(usize, usize, (i32, i32)),
+    ///a creature leaves the world by starving or culling
+    #[serde(rename = "d")]
+    Death(usize, (i32, i32), DeathCause),
+    ///a plant grows
+    #[serde(rename = "p")]
+    Plant((i32, i32)),
+    ///a creature eats the plant on its cell
+    #[serde(rename = "g")]
+    Graze(usize, (i32, i32)),
+    ///a plant is removed at the end of an epoch
+    #[serde(rename = "w")]
+    Wither((i32, i32)),
+}
+
+/// the first line of an event file
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    world: World,
+    species: Vec<String>,
+}
+
+/// all events of a step. step 0 of an epoch holds what happened before its first step:
+/// the placing of the first generation or the selection and birth of the next one
+#[derive(Debug, Serialize, Deserialize)]
+struct Frame {
+    epoch: u16,
+    step: u16,
+    events: Vec<Event>,
+}
+
+/// writes `<name>.events.jsonl`, a header line and one line per frame
+pub struct EventLog {
+    file: BufWriter<File>,
+    pending: Vec<Event>,
+}
+
+impl EventLog {
+    pub fn create(path: &Path, world: &World, species: Vec<String>) -> EventLog {
+        let mut file = BufWriter::new(File::create(path).expect("file problem"));
+        let header = Header { world: world.clone(), species };
+        writeln!(file, "{}", serde_json::to_string(&header).expect("header problem")).expect("write went wrong");
+        EventLog { file, pending: vec![] }
+    }
+
+    pub fn push(&mut self, event: Event) {
+        self.pending.push(event);
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    ///writes everything pushed since the last frame as the frame `step` of `epoch`
+    pub fn frame(&mut self, epoch: u16, step: u16) {
+        let frame = Frame { epoch, step, events: std::mem::take(&mut self.pending) };
+        writeln!(self.file, "{}", serde_json::to_string(&frame).expect("frame problem")).expect("write went wrong");
+    }
+
+    pub fn finish(&mut self) {
+        self.file.flush().expect("write went wrong");
+    }
+}
+
+/// what a replay shows at one moment
+struct State {
+    plants: HashSet<(i32, i32)>,
+    species: Vec<HashSet<(i32, i32)>>,
+}
+
+impl State {
+    fn apply(&mut self, event: &Event) {
+        match *event {
+            Event::Spawn(s, pos) => { self.species[s].insert(pos); }
+            Event::Move(s, from, to) => {
+                self.species[s].remove(&from);
+                self.species[s].insert(to);
+            }
+            Event::Eat(_, s, pos) | Event::Death(s, pos, _) => { self.species[s].remove(&pos); }
+            Event::Plant(pos) => { self.plants.insert(pos); }
+            Event::Graze(_, pos) | Event::Wither(pos) => { self.plants.remove(&pos); }
+        }
+    }
+
+    fn undo(&mut self, event: &Event) {
+        match *event {
+            Event::Spawn(s, pos) => { self.species[s].remove(&pos); }
+            Event::Move(s, from, to) => {
+                self.species[s].remove(&to);
+                self.species[s].insert(from);
+            }
+            Event::Eat(_, s, pos) | Event::Death(s, pos, _) => { self.species[s].insert(pos); }
+            Event::Plant(pos) => { self.plants.remove(&pos); }
+            Event::Graze(_, pos) | Event::Wither(pos) => { self.plants.insert(pos); }
+        }
+    }
+}
+
+/// draws the frames `from` to `to` of an event file, backward if `to` comes first.
+/// missing bounds mean the first or last frame
+pub fn replay(path: &Path, from: Option<(u16, u16)>, to: Option<(u16, u16)>, fps: f32) {
+    let text = fs::read_to_string(path).expect("could not read event file");
+    let mut lines = text.lines();
+    let header: Header = serde_json::from_str(lines.next().unwrap_or_default()).expect("invalid event file header");
+    let frames: Vec<Frame> = lines.map(|l| serde_json::from_str(l).expect("invalid frame")).collect();
+    if frames.is_empty() {
+        println!("no frames in {}", path.display());
+        return;
+    }
+    let index = |at: Option<(u16, u16)>, default: usize| match at {
+        Some(at) => frames.iter().position(|f| (f.epoch, f.step) >= at).unwrap_or(frames.len() - 1),
+        None => default,
+    };
+    let (start, end) = (index(from, 0), index(to, frames.len() - 1));
+    let mut state = State { plants: HashSet::new(), species: vec![HashSet::new(); header.species.len()] };
+    for frame in &frames[..=start] {
+        frame.events.iter().for_each(|e| state.apply(e));
+    }
+    let letters: Vec<char> = header.species.iter().map(|n| letter(n)).collect();
+    let delay = time::Duration::from_secs_f32(1.0 / fps.max(0.001));
+    let mut i = start;
+    loop {
+        let used = state.plants.iter().chain(state.species.iter().flatten()).cloned();
+        draw(&header.world, used, |pos| state.plants.contains(pos), |pos| {
+            (0..letters.len()).rev().find(|s| state.species[*s].contains(pos)).map(|s| letters[s])
+        });
+        let counts: Vec<String> = header.species.iter().zip(&state.species).map(|(n, s)| format!("{}: {}", n, s.len())).collect();
+        println!("epoch: {} simulation step: {} -> {}", frames[i].epoch, frames[i].step, counts.join(" "));
+        if i == end {
+            break;
+        }
+        thread::sleep(delay);
+        if start <= end {
+            i += 1;
+            frames[i].events.iter().for_each(|e| state.apply(e));
+        } else {
+            frames[i].events.iter().rev().for_each(|e| state.undo(e));
+            i -= 1;
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//!
+//! everything related to the ancestry of the creatures
+//!
+use std::ops::{Deref, DerefMut};
+
+use serde::{Deserialize, Serialize};
+
+/// a genome living in the world, known to the genealogy by its id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Creature<G> {
+    pub id: u64,
+    pub genome: G,
+}
+
+impl<G> Deref for Creature<G> {
+    type Target = G;
+    fn deref(&self) -> &G {
+        &self.genome
+    }
+}
+
+impl<G> DerefMut for Creature<G> {
+    fn deref_mut(&mut self) -> &mut G {
+        &mut self.genome
+    }
+}
+
+/// why a creature left the world
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeathCause {
+    ///did not gather enough energy during its epoch
+    Starved,
+    ///was eaten by a hunter
+    Eaten,
+    ///survived the selection and was replaced by the next generation
+    Culled,
+}
+
+impl DeathCause {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeathCause::Starved => "starved",
+            DeathCause::Eaten => "eaten",
+            DeathCause::Culled => "culled",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DeathCause> {
+        [DeathCause::Starved, DeathCause::Eaten, DeathCause::Culled].into_iter().find(|c| c.name() == name)
+    }
+}
+
+/// the life of one creature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lineage {
+    pub species: usize,
+    ///None for the founders placed at the start
+    pub parents: Option<(u64, u64)>,
+    ///the first epoch the creature lives in
+    pub born: u16,
+    ///the epoch it died in and why, None while it is alive
+    pub death: Option<(u16, DeathCause)>,
+}
+
+/// every creature that ever lived, the id is the index.
+/// parents always have a smaller id than their children
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Genealogy {
+    creatures: Vec<Lineage>,
+}
+
+impl Genealogy {
+    pub fn new() -> Genealogy {
+        Genealogy::default()
+    }
+
+    ///registers a new creature and returns its id
+    pub fn birth(&mut self, species: usize, parents: Option<(u64, u64)>, born: u16) -> u64 {
+        self.creatures.push(Lineage { species, parents, born, death: None });
+        self.creatures.len() as u64 - 1
+    }
+
+    pub fn death(&mut self, id: u64, epoch: u16, cause: DeathCause) {
+        self.creatures[id as usize].death = Some((epoch, cause));
+    }
+
+    pub fn get(&self, id: u64) -> &Lineage {
+        &self.creatures[id as usize]
+    }
+
+    ///every creature with its id, in the order they were born
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &Lineage)> {
+        self.creatures.iter().enumerate().map(|(id, l)| (id as u64, l))
+    }
+
+    ///the founders of every species with the number of living creatures descending from them,
+    ///founders without living descendants are left out
+    pub fn surviving_founders(&self) -> Vec<(u64, usize)> {
+        let mut descendants = vec![0; self.creatures.len()];
+        let mut seen = vec![usize::MAX; self.creatures.len()];
+        let alive = self.iter().filter(|(_, l)| l.death.is_none()).map(|(id, _)| id as usize);
+        for (n, id) in alive.enumerate() {
+            //walks up the family tree, `seen` marks what was visited for this creature
+            let mut stack = vec![id];
+            while let Some(c) = stack.pop() {
+                if seen[c] == n {
+                    continue;
+                }
+                seen[c] = n;
+                match self.creatures[c].parents {
+                    Some((a, b)) => stack.extend([a as usize, b as usize]),
+                    None => descendants[c] += 1,
+                }
+            }
+        }
+        descendants.into_iter().enumerate()
+            .filter(|(_, d)| *d > 0)
+            .map(|(id, d)| (id as u64, d))
+            .collect()
+    }
+}
@@ -1,122 +1,276 @@
+use std::{collections::BTreeMap, f32::consts::PI};
+
 use rand::Rng;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::config::SpeciesConfig;
+use crate::schema::{Gene, GeneConfig, GeneType, Schema};
 /// 
 /// everything related to genome
 /// 
 /// 
 /// 
 const MUTATION_DIVISION: i32 = 1000;// chance value of 1 equals a mutation chance of 0.01%
+/// the built in genes of the basic genome, the first ones of every schema
+pub const GENES: [&str; 7] = ["weight", "speed", "power", "detection", "eval_1", "eval_2", "eval_3"];
+/// whether the fixed crossover takes a built in gene from the second parent
+const FIXED: [bool; 7] = [false, true, false, true, true, false, true];
+/// the self-adaptive mutation rate stays between these factors
+const RATE_LIMITS: (f32, f32) = (0.01, 100.0);
 
-/// Genome trait
-pub trait Genome{
-    fn new(e: EatingType) -> Self;
-    fn mutate(&mut self, chance: i32);
-    fn crossover(&self, other: &Self) -> Self;
-    fn to_string(&self) -> String;
+/// Genome trait, serializable so a running simulation can be saved
+pub trait Genome: Serialize + DeserializeOwned {
+    ///what the genomes of a species share from one generation to the next, like the innovations of NEAT
+    type Shared: Default + Serialize + DeserializeOwned;
+    ///the genes the genome of `species` carries, the basic genes with the ones of the config by default
+    fn schema(config: &[GeneConfig], _species: &SpeciesConfig) -> Result<Schema, String> {
+        Schema::new(config)
+    }
+    fn new<R: Rng>(e: EatingType, schema: &Schema, config: &SpeciesConfig, rng: &mut R) -> Self;
+    fn mutate<R: Rng>(&mut self, chance: i32, schema: &Schema, config: &SpeciesConfig, shared: &mut Self::Shared, rng: &mut R);
+    fn crossover<R: Rng>(&self, other: &Self, schema: &Schema, method: Crossover, rng: &mut R) -> Self;
+    fn to_string(&self, schema: &Schema) -> String;
     fn get_detection_range(&self) -> f32;
     //fn evaluate_plant(plant: i32);
     //fn compare_strength(&self, other: &Self) -> f32;
     fn increase_energy(&mut self, energy: f32);
-    fn has_enough_energy(&self) -> bool;
+    ///whether the energy covers the costs of the genes
+    fn has_enough_energy(&self, schema: &Schema) -> bool;
+    fn get_energy(&self) -> f32;
     fn get_weight(&self) -> f32;
     fn get_power(&self) -> f32;
     fn get_speed(&self) -> f32;
     fn get_eval(&self, num: u8) -> i32;
-    //fn choose_direction(())
+    ///the value of every gene of the schema followed by the mutation rate, the order of `Schema::names`
+    fn genes(&self) -> Vec<f32>;
+    ///a score for every direction (left, right, up, down) from what the creature senses,
+    ///none leaves the choice to the eval weights
+    fn choose_direction(&self, _senses: &Senses) -> Option<[f32; 4]> {
+        None
+    }
+    ///called with the parents before their children are bred
+    fn prepare_breeding(_parents: &[&Self], _config: &SpeciesConfig, _shared: &mut Self::Shared) {}
+    ///the indices of the two parents of the next child, any two of the parents by default
+    fn choose_parents<R: Rng>(parents: &[&Self], _shared: &Self::Shared, rng: &mut R) -> (usize, usize) {
+        (rng.gen_range(0..parents.len()), rng.gen_range(0..parents.len()))
+    }
+    ///a line for the log about the shared state after breeding, if there is anything to tell
+    fn describe_shared(_shared: &Self::Shared) -> Option<String> {
+        None
+    }
+}
+
+/// what a creature perceives inside its detection range
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Senses {
+    ///the offset to the nearest plant, prey and predator, none if there is none in sight
+    pub plant: Option<(i32, i32)>,
+    pub prey: Option<(i32, i32)>,
+    pub predator: Option<(i32, i32)>,
+    ///the detection range the offsets are within
+    pub range: f32,
+    pub energy: f32,
+}
+
+/// how the genes of two parents are combined into a child, chosen per species
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Crossover {
+    ///weight, power and eval 2 from the first parent, the other genes from the second
+    #[default]
+    Fixed,
+    ///every gene from a randomly chosen parent
+    Uniform,
+    ///the genes before a random cut from the first parent, the rest from the second
+    OnePoint,
+    ///the genes between two random cuts from the second parent, the rest from the first
+    TwoPoint,
+    ///the float genes mixed with one random weight, the others like uniform
+    Arithmetic,
+    ///BLX-α: every float gene drawn from the range of both parents widened by α times its
+    ///width on each side, the others like uniform
+    Blend(f32),
+    ///a copy of the first parent
+    Clone,
+}
+
+/// how a child mutates, chosen per species
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Mutation {
+    ///every gene mutates on its own with its rate, instead of one random gene with the mutation chance
+    pub per_gene: bool,
+    ///the chance of every gene to mutate with `per_gene`, the rate of the schema or the
+    ///mutation chance for the missing ones
+    pub rates: BTreeMap<String, f32>,
+    pub step: Step,
+    ///the size of the steps of the float genes without a sigma in the schema: half the range
+    ///for uniform, the standard deviation for gaussian and the scale for cauchy
+    pub sigma: f32,
+    ///the same for the int genes, like the eval weights
+    pub eval_sigma: f32,
+    ///the range of genes, the bounds of the schema are kept anyway
+    pub bounds: BTreeMap<String, Bound>,
+    ///every creature carries a factor of its mutation chance, which itself mutates log-normally
+    ///with `tau` and is inherited as the geometric mean of the parents
+    pub self_adaptive: bool,
+    pub tau: f32,
+}
+
+impl Default for Mutation {
+    fn default() -> Self {
+        Mutation {
+            per_gene: false,
+            rates: BTreeMap::new(),
+            step: Step::Uniform,
+            sigma: 1.0,
+            eval_sigma: 50.0,
+            bounds: BTreeMap::new(),
+            self_adaptive: false,
+            tau: 0.3,
+        }
+    }
+}
+
+/// the distribution of a mutation step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Step {
+    #[default]
+    Uniform,
+    Gaussian,
+    ///heavy tailed, mostly small steps and now and then a very large one
+    Cauchy,
+}
+
+/// the range of a gene, a missing end leaves that side open
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bound {
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    ///mirrors values beyond an end back into the range instead of setting them to it
+    pub reflect: bool,
+}
+
+impl Bound {
+    fn apply(&self, value: f32) -> f32 {
+        let (min, max) = (self.min.unwrap_or(f32::NEG_INFINITY), self.max.unwrap_or(f32::INFINITY));
+        let mut v = value;
+        if self.reflect {
+            if v < min {
+                v = min + (min - v);
+            }
+            if v > max {
+                v = max - (v - max);
+            }
+        }
+        //a step of more than the whole range stays at the end it went beyond
+        v.clamp(min, max)
+    }
 }
 
-/// basic genome struct
-#[derive(Debug, Clone)]
+/// basic genome struct, the genes follow the order of the schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BasicGenome {
     etype: EatingType,
-    weight: f32, //simbolieses the size of the creature
-    speed: f32,
-    power: f32,
-    detection: f32,
+    ///weight simbolieses the size of the creature, the eval weights are whole numbers
+    genes: Vec<f32>,
     energy: f32,
-    eval_weight_1: i32,
-    eval_weight_2: i32,
-    eval_weight_3: i32,
+    ///factor of the mutation chance, only changes with a self-adaptive mutation
+    #[serde(default = "default_rate")]
+    mutation_rate: f32,
 }
 //
 impl Genome for BasicGenome {
-    fn new(etype: EatingType) -> Self {
-        let mut rng = rand::thread_rng();
-        let max1 = 5.0;
-        let max2 = 1000;
-        let min = -1 * max2;
-        
+    type Shared = ();
+
+    fn new<R: Rng>(etype: EatingType, schema: &Schema, _config: &SpeciesConfig, rng: &mut R) -> Self {
+        let genes = schema.genes.iter().map(|g| match g.kind {
+            GeneType::Float => rng.gen_range(g.init[0]..g.init[1]),
+            GeneType::Int => rng.gen_range(g.init[0] as i32..g.init[1] as i32) as f32,
+        }).collect();
         BasicGenome{
             etype,
-            weight: rng.gen_range(0.1..max1),
-            speed: rng.gen_range(0.0..max1),
-            power: rng.gen_range(0.0..max1),
-            detection: rng.gen_range(0.0..max1),
+            genes,
             energy: 0.0,
-            eval_weight_1: rng.gen_range(min..max2),
-            eval_weight_2: rng.gen_range(min..max2),
-            eval_weight_3: rng.gen_range(min..max2),
-        }
-    }
-    fn mutate(&mut self, chance: i32) {
-        //let chance = 5;
-        let mut rng = rand::thread_rng();
-        if chance < rng.gen_range(0..MUTATION_DIVISION) {
-            return;
-        }
-        let from = -1.0;
-        let to = 1.0;
-        let ifrom = -50;
-        let ito = 50;
-        let choosen = rng.gen_range(0..7);
-        match choosen {
-            0 => self.weight = mutate_f32_gene(self.weight, from, to),
-            1 => self.speed = mutate_f32_gene(self.speed, from, to),
-            2 => self.power = mutate_f32_gene(self.power, from, to),
-            3 => self.detection = mutate_f32_gene(self.detection, from, to),
-            4 => self.eval_weight_1 = mutate_i32_gene(self.eval_weight_1, ifrom, ito),
-            5 => self.eval_weight_2 = mutate_i32_gene(self.eval_weight_2, ifrom, ito),
-            6 => self.eval_weight_3 = mutate_i32_gene(self.eval_weight_3, ifrom, ito),
-            _ => panic!("choosen gene does not exist")
-        }
-        if self.weight < 0.1 {
-            self.weight = 0.1;
-        }
-        if self.power < 0.0 {
-            self.power = 0.0;
-        }
-        if self.speed < 0.0 {
-            self.speed = 0.0;
+            mutation_rate: 1.0,
         }
-        
-        if self.detection < 0.0 {
-            self.detection = 0.0;
+    }
+    fn mutate<R: Rng>(&mut self, chance: i32, schema: &Schema, config: &SpeciesConfig, _shared: &mut (), rng: &mut R) {
+        let model = &config.mutation;
+        if model.self_adaptive {
+            self.mutation_rate = (self.mutation_rate * (model.tau * standard_normal(rng)).exp()).clamp(RATE_LIMITS.0, RATE_LIMITS.1);
         }
-        
+        let chance = chance as f32 * self.mutation_rate;
+        if model.per_gene {
+            for (i, gene) in schema.genes.iter().enumerate() {
+                let rate = model.rates.get(&gene.name).or(gene.rate.as_ref()).map_or(chance / MUTATION_DIVISION as f32, |r| r * self.mutation_rate);
+                if rng.gen::<f32>() < rate {
+                    self.genes[i] += mutation_step(gene, model, rng);
+                }
+            }
+        } else if chance >= rng.gen_range(0..MUTATION_DIVISION) as f32 {
+            let choosen = rng.gen_range(0..schema.len());
+            self.genes[choosen] += mutation_step(&schema.genes[choosen], model, rng);
+        }
+        for (i, gene) in schema.genes.iter().enumerate() {
+            if let Some(bound) = model.bounds.get(&gene.name) {
+                self.genes[i] = bound.apply(self.genes[i]);
+            }
+        }
+        self.bound_genes(schema);
     }
 
-    fn crossover(&self, other: &Self) -> Self {
-
-        BasicGenome {
+    fn crossover<R: Rng>(&self, other: &Self, schema: &Schema, method: Crossover, rng: &mut R) -> Self {
+        let (a, b, n) = (&self.genes, &other.genes, schema.len());
+        //for every gene whether it comes from the second parent
+        let from_other: Vec<bool> = match method {
+            Crossover::Fixed => (0..n).map(|i| if i < FIXED.len() { FIXED[i] } else { i % 2 == 1 }).collect(),
+            Crossover::Clone => vec![false; n],
+            Crossover::OnePoint => {
+                let cut = rng.gen_range(1..n);
+                (0..n).map(|i| i >= cut).collect()
+            }
+            Crossover::TwoPoint => {
+                let first = rng.gen_range(1..n - 1);
+                let second = rng.gen_range(first + 1..n);
+                (0..n).map(|i| first <= i && i < second).collect()
+            }
+            Crossover::Uniform | Crossover::Arithmetic | Crossover::Blend(_) => (0..n).map(|_| rng.gen_bool(0.5)).collect(),
+        };
+        let mut genes: Vec<f32> = (0..n).map(|i| if from_other[i] { b[i] } else { a[i] }).collect();
+        let floats = schema.genes.iter().enumerate().filter(|(_, g)| g.kind == GeneType::Float).map(|(i, _)| i);
+        match method {
+            Crossover::Arithmetic => {
+                let share = rng.gen_range(0.0..=1.0);
+                for i in floats {
+                    genes[i] = share * a[i] + (1.0 - share) * b[i];
+                }
+            }
+            Crossover::Blend(alpha) => {
+                for i in floats {
+                    let (lo, hi) = (a[i].min(b[i]), a[i].max(b[i]));
+                    let widen = alpha * (hi - lo);
+                    if hi - lo > 0.0 {
+                        genes[i] = rng.gen_range(lo - widen..=hi + widen);
+                    }
+                }
+            }
+            _ => {}
+        }
+        let mut child = BasicGenome {
             etype: self.etype.clone(),
-            weight: self.weight,
-            speed: other.speed,
-            power: self.power,
-            detection: other.detection,
+            genes,
             energy: 0.0,
-            eval_weight_1: other.eval_weight_1,
-            eval_weight_2: self.eval_weight_2,
-            eval_weight_3: other.eval_weight_3,
-        }
+            mutation_rate: (self.mutation_rate * other.mutation_rate).sqrt(),
+        };
+        child.bound_genes(schema);
+        child
     }
 
-    fn to_string(&self) -> String {
-        let name: String;
-        match &self.etype {
-            EatingType::Herbivore => name = String::from("Herbivore"),
-            EatingType::Carnivore => name = String::from("Carnivore"),
-            EatingType::Omnivore => name = String::from("Omnivore")
-        };
-        format!("{}: [w: {}, s: {}, p: {}, d: {}, eval: 1:{} 2:{} 3:{}]",name, self.weight, self.speed, self.power, self.detection, self.eval_weight_1, self.eval_weight_2, self.eval_weight_3)
+    fn to_string(&self, schema: &Schema) -> String {
+        self.describe(&schema.genes)
     }
 /*
     fn evaluate_creature(&self, other: &Self) -> i32 {
@@ -144,42 +298,79 @@ impl Genome for BasicGenome {
     }*/
 
     fn get_detection_range(&self) -> f32 {
-        self.detection
+        self.genes[3]
     }
 
     fn increase_energy(&mut self, energy: f32) {
         self.energy += energy;
     }
 
-    fn has_enough_energy(&self) -> bool {
-        0.0 <= self.energy - 0.2 *(self.weight + self.power + self.detection + self.speed)
+    fn has_enough_energy(&self, schema: &Schema) -> bool {
+        let need: f32 = schema.genes.iter().zip(&self.genes).map(|(g, v)| g.cost * v).sum();
+        0.0 <= self.energy - need
     }
 
     fn get_power(&self) -> f32 {
-        self.power 
+        self.genes[2]
+    }
+
+    fn get_energy(&self) -> f32 {
+        self.energy
     }
 
     fn get_speed(&self) -> f32 {
-        self.speed
+        self.genes[1]
     }
 
     fn get_weight(&self) -> f32 {
-        self.weight
+        self.genes[0]
     }
 
     fn get_eval(&self, num: u8) -> i32 {
         
         match num {
-            1 => self.eval_weight_1,
-            2 => self.eval_weight_2,
-            3 => self.eval_weight_3,
+            1..=3 => self.genes[3 + num as usize] as i32,
             _ => panic!("wrong eval num")
         }
     }
 
+    fn genes(&self) -> Vec<f32> {
+        self.genes.iter().copied().chain([self.mutation_rate]).collect()
+    }
+
 }
 
-#[derive(Debug, Clone)]
+impl BasicGenome {
+    ///the genes in the order of the schema
+    pub fn values(&self) -> &[f32] {
+        &self.genes
+    }
+
+    ///the genome as text with the first genes of the schema, as many as `genes` has
+    pub fn describe(&self, genes: &[Gene]) -> String {
+        let name = match &self.etype {
+            EatingType::Herbivore => String::from("Herbivore"),
+            EatingType::Carnivore => String::from("Carnivore"),
+            EatingType::Omnivore => String::from("Omnivore")
+        };
+        let g = &self.genes;
+        let extra: String = genes.iter().zip(g).skip(GENES.len()).map(|(gene, v)| format!(", {}: {}", gene.name, v)).collect();
+        format!("{}: [w: {}, s: {}, p: {}, d: {}, eval: 1:{} 2:{} 3:{}{}]",name, g[0], g[1], g[2], g[3], g[4], g[5], g[6], extra)
+    }
+
+    ///keeps the genes in the range of the schema, the int genes whole
+    fn bound_genes(&mut self, schema: &Schema) {
+        for (v, gene) in self.genes.iter_mut().zip(&schema.genes) {
+            *v = gene.bounds.apply(*v);
+            if gene.kind == GeneType::Int {
+                //adding 0 turns -0 into 0
+                *v = v.trunc() + 0.0;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(warnings)]
 pub enum EatingType {
     Carnivore,
@@ -187,18 +378,169 @@ pub enum EatingType {
     Omnivore
 }
 
-fn mutate_f32_gene(gene: f32, from: f32, to: f32) -> f32 {
-    if from >= to {
-        panic!("Ilegal argument: range to small");
-    }
-    let mut rng = rand::thread_rng();
-    gene + rng.gen_range(from..to+1.0)
+fn default_rate() -> f32 {
+    1.0
+}
+
+/// the change of `gene`, int genes move in whole steps
+fn mutation_step<R: Rng>(gene: &Gene, model: &Mutation, rng: &mut R) -> f32 {
+    let int = gene.kind == GeneType::Int;
+    let sigma = gene.sigma.unwrap_or(if int { model.eval_sigma } else { model.sigma });
+    let step = match model.step {
+        Step::Uniform if int => rng.gen_range(-sigma.round() as i32..=sigma.round() as i32) as f32,
+        Step::Uniform => rng.gen_range(-sigma..=sigma),
+        Step::Gaussian => sigma * standard_normal(rng),
+        Step::Cauchy => sigma * (PI * (rng.gen::<f32>() - 0.5)).tan(),
+    };
+    if int { step.round() } else { step }
+}
+
+/// box-muller transform of two uniform numbers
+pub fn standard_normal<R: Rng>(rng: &mut R) -> f32 {
+    let u: f32 = 1.0 - rng.gen::<f32>();
+    let v: f32 = rng.gen();
+    (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
 }
 
-fn mutate_i32_gene(gene: i32, from: i32, to: i32) -> i32 {
-    if from >= to {
-        panic!("Ilegal argument: range to small");
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    ///a schema with an added float and int gene and two parents whose genes all differ
+    fn parents() -> (Schema, BasicGenome, BasicGenome) {
+        let schema = Schema::new(&[
+            GeneConfig { name: "fur".to_owned(), ..Default::default() },
+            GeneConfig { name: "teeth".to_owned(), kind: Some(GeneType::Int), init: Some([0.0, 10.0]), ..Default::default() },
+        ]).expect("schema");
+        let parent = |float: f32, int: f32| BasicGenome {
+            etype: EatingType::Herbivore,
+            genes: schema.genes.iter().enumerate().map(|(i, g)| match g.kind {
+                GeneType::Float => float + i as f32 * 0.1,
+                GeneType::Int => int + i as f32,
+            }).collect(),
+            energy: 0.0,
+            mutation_rate: 1.0,
+        };
+        let (a, b) = (parent(1.0, 100.0), parent(3.0, -50.0));
+        (schema, a, b)
+    }
+
+    ///for every gene whether it is the one of the first or the second parent, none for neither
+    fn provenance(child: &BasicGenome, a: &BasicGenome, b: &BasicGenome) -> Vec<Option<bool>> {
+        child.genes.iter().zip(a.genes.iter().zip(&b.genes))
+            .map(|(c, (a, b))| if c == a { Some(false) } else if c == b { Some(true) } else { None })
+            .collect()
+    }
+
+    fn children(method: Crossover) -> (Schema, BasicGenome, BasicGenome, Vec<BasicGenome>) {
+        let (schema, a, b) = parents();
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let children = (0..200).map(|_| a.crossover(&b, &schema, method, &mut rng)).collect();
+        (schema, a, b, children)
+    }
+
+    fn ints_whole(schema: &Schema, child: &BasicGenome) -> bool {
+        schema.genes.iter().zip(&child.genes).all(|(g, v)| g.kind == GeneType::Float || v.fract() == 0.0)
+    }
+
+    #[test]
+    fn fixed_follows_the_mask() {
+        let (schema, a, b, children) = children(Crossover::Fixed);
+        let mask: Vec<Option<bool>> = (0..schema.len()).map(|i| Some(if i < FIXED.len() { FIXED[i] } else { i % 2 == 1 })).collect();
+        for child in &children {
+            assert_eq!(provenance(child, &a, &b), mask);
+        }
+    }
+
+    #[test]
+    fn clone_copies_the_first_parent() {
+        let (_, a, _, children) = children(Crossover::Clone);
+        for child in &children {
+            assert_eq!(child.genes, a.genes);
+        }
+    }
+
+    #[test]
+    fn one_point_takes_the_second_parent_after_the_cut() {
+        let (schema, a, b, children) = children(Crossover::OnePoint);
+        let mut cuts = vec![];
+        for child in &children {
+            let from: Vec<bool> = provenance(child, &a, &b).into_iter().map(|p| p.expect("gene of neither parent")).collect();
+            let cut = from.iter().position(|f| *f).expect("no gene of the second parent");
+            assert!(cut >= 1);
+            assert!(from[cut..].iter().all(|f| *f) && from[..cut].iter().all(|f| !f));
+            cuts.push(cut);
+        }
+        assert!((1..schema.len()).all(|c| cuts.contains(&c)));
+    }
+
+    #[test]
+    fn two_point_takes_the_second_parent_between_the_cuts() {
+        let (schema, a, b, children) = children(Crossover::TwoPoint);
+        for child in &children {
+            let from: Vec<bool> = provenance(child, &a, &b).into_iter().map(|p| p.expect("gene of neither parent")).collect();
+            let first = from.iter().position(|f| *f).expect("no gene of the second parent");
+            let second = first + from[first..].iter().take_while(|f| **f).count();
+            assert!(first >= 1 && second <= schema.len());
+            assert!(from[..first].iter().chain(&from[second..]).all(|f| !f));
+        }
+    }
+
+    #[test]
+    fn uniform_takes_every_gene_from_a_parent() {
+        let (schema, a, b, children) = children(Crossover::Uniform);
+        let provenances: Vec<Vec<Option<bool>>> = children.iter().map(|c| provenance(c, &a, &b)).collect();
+        for i in 0..schema.len() {
+            assert!(provenances.iter().all(|p| p[i].is_some()));
+            assert!(provenances.iter().any(|p| p[i] == Some(false)) && provenances.iter().any(|p| p[i] == Some(true)));
+        }
     }
-    let mut rng = rand::thread_rng();
-    gene + rng.gen_range(from..to+1)
-}
\ No newline at end of file
+
+    #[test]
+    fn arithmetic_lies_between_the_parents() {
+        let (schema, a, b, children) = children(Crossover::Arithmetic);
+        for child in &children {
+            for (i, gene) in schema.genes.iter().enumerate() {
+                let (lo, hi) = (a.genes[i].min(b.genes[i]), a.genes[i].max(b.genes[i]));
+                match gene.kind {
+                    GeneType::Float => assert!(lo - 1e-5 <= child.genes[i] && child.genes[i] <= hi + 1e-5),
+                    GeneType::Int => assert!(child.genes[i] == lo || child.genes[i] == hi),
+                }
+            }
+            assert!(ints_whole(&schema, child));
+        }
+    }
+
+    #[test]
+    fn blend_stays_in_the_widened_range() {
+        let alpha = 0.5;
+        let (schema, a, b, children) = children(Crossover::Blend(alpha));
+        let mut outside = false;
+        for child in &children {
+            for (i, gene) in schema.genes.iter().enumerate() {
+                let (lo, hi) = (a.genes[i].min(b.genes[i]), a.genes[i].max(b.genes[i]));
+                let widen = alpha * (hi - lo);
+                match gene.kind {
+                    GeneType::Float => {
+                        assert!(lo - widen <= child.genes[i] && child.genes[i] <= hi + widen);
+                        outside |= child.genes[i] < lo || child.genes[i] > hi;
+                    }
+                    GeneType::Int => assert!(child.genes[i] == lo || child.genes[i] == hi),
+                }
+            }
+            assert!(ints_whole(&schema, child));
+        }
+        assert!(outside);
+    }
+
+    #[test]
+    fn int_genes_stay_whole() {
+        for method in [Crossover::Fixed, Crossover::Uniform, Crossover::OnePoint, Crossover::TwoPoint, Crossover::Arithmetic, Crossover::Blend(0.5), Crossover::Clone] {
+            let (schema, _, _, children) = children(method);
+            assert!(children.iter().all(|c| ints_whole(&schema, c)));
+        }
+    }
+}
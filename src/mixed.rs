@@ -0,0 +1,174 @@
+//!
+//! everything related to food webs whose species use different genomes
+//!
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{GenomeKind, SpeciesConfig};
+use crate::genome::{BasicGenome, Crossover, EatingType, Genome, Senses};
+use crate::neat::{Innovations, NeatGenome};
+use crate::neural::NeuralGenome;
+use crate::schema::{GeneConfig, Schema};
+
+/// the genome a species chose with `genome` in its config, so a neat herbivore can
+/// live next to a basic carnivore in the same run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MixedGenome {
+    Basic(BasicGenome),
+    Neural(NeuralGenome),
+    Neat(NeatGenome),
+}
+
+impl MixedGenome {
+    ///the neat genomes of `parents`, all of them belong to a neat species
+    fn neat<'a>(parents: &[&'a Self]) -> Vec<&'a NeatGenome> {
+        parents.iter().map(|p| match p {
+            MixedGenome::Neat(g) => g,
+            _ => panic!("a neat species with another genome"),
+        }).collect()
+    }
+}
+
+impl Genome for MixedGenome {
+    ///the innovations and species of a neat species, nothing for the others
+    type Shared = Option<Innovations>;
+
+    fn schema(config: &[GeneConfig], species: &SpeciesConfig) -> Result<Schema, String> {
+        match species.genome {
+            GenomeKind::Basic => BasicGenome::schema(config, species),
+            GenomeKind::Neural => NeuralGenome::schema(config, species),
+            GenomeKind::Neat => NeatGenome::schema(config, species),
+        }
+    }
+
+    fn new<R: Rng>(e: EatingType, schema: &Schema, config: &SpeciesConfig, rng: &mut R) -> Self {
+        match config.genome {
+            GenomeKind::Basic => MixedGenome::Basic(BasicGenome::new(e, schema, config, rng)),
+            GenomeKind::Neural => MixedGenome::Neural(NeuralGenome::new(e, schema, config, rng)),
+            GenomeKind::Neat => MixedGenome::Neat(NeatGenome::new(e, schema, config, rng)),
+        }
+    }
+
+    fn mutate<R: Rng>(&mut self, chance: i32, schema: &Schema, config: &SpeciesConfig, shared: &mut Option<Innovations>, rng: &mut R) {
+        match self {
+            MixedGenome::Basic(g) => g.mutate(chance, schema, config, &mut (), rng),
+            MixedGenome::Neural(g) => g.mutate(chance, schema, config, &mut (), rng),
+            MixedGenome::Neat(g) => g.mutate(chance, schema, config, shared.get_or_insert_with(Innovations::default), rng),
+        }
+    }
+
+    fn crossover<R: Rng>(&self, other: &Self, schema: &Schema, method: Crossover, rng: &mut R) -> Self {
+        match (self, other) {
+            (MixedGenome::Basic(a), MixedGenome::Basic(b)) => MixedGenome::Basic(a.crossover(b, schema, method, rng)),
+            (MixedGenome::Neural(a), MixedGenome::Neural(b)) => MixedGenome::Neural(a.crossover(b, schema, method, rng)),
+            (MixedGenome::Neat(a), MixedGenome::Neat(b)) => MixedGenome::Neat(a.crossover(b, schema, method, rng)),
+            _ => panic!("parents with different genomes"),
+        }
+    }
+
+    fn to_string(&self, schema: &Schema) -> String {
+        match self {
+            MixedGenome::Basic(g) => g.to_string(schema),
+            MixedGenome::Neural(g) => g.to_string(schema),
+            MixedGenome::Neat(g) => g.to_string(schema),
+        }
+    }
+
+    fn get_detection_range(&self) -> f32 {
+        match self {
+            MixedGenome::Basic(g) => g.get_detection_range(),
+            MixedGenome::Neural(g) => g.get_detection_range(),
+            MixedGenome::Neat(g) => g.get_detection_range(),
+        }
+    }
+
+    fn increase_energy(&mut self, energy: f32) {
+        match self {
+            MixedGenome::Basic(g) => g.increase_energy(energy),
+            MixedGenome::Neural(g) => g.increase_energy(energy),
+            MixedGenome::Neat(g) => g.increase_energy(energy),
+        }
+    }
+
+    fn has_enough_energy(&self, schema: &Schema) -> bool {
+        match self {
+            MixedGenome::Basic(g) => g.has_enough_energy(schema),
+            MixedGenome::Neural(g) => g.has_enough_energy(schema),
+            MixedGenome::Neat(g) => g.has_enough_energy(schema),
+        }
+    }
+
+    fn get_energy(&self) -> f32 {
+        match self {
+            MixedGenome::Basic(g) => g.get_energy(),
+            MixedGenome::Neural(g) => g.get_energy(),
+            MixedGenome::Neat(g) => g.get_energy(),
+        }
+    }
+
+    fn get_weight(&self) -> f32 {
+        match self {
+            MixedGenome::Basic(g) => g.get_weight(),
+            MixedGenome::Neural(g) => g.get_weight(),
+            MixedGenome::Neat(g) => g.get_weight(),
+        }
+    }
+
+    fn get_power(&self) -> f32 {
+        match self {
+            MixedGenome::Basic(g) => g.get_power(),
+            MixedGenome::Neural(g) => g.get_power(),
+            MixedGenome::Neat(g) => g.get_power(),
+        }
+    }
+
+    fn get_speed(&self) -> f32 {
+        match self {
+            MixedGenome::Basic(g) => g.get_speed(),
+            MixedGenome::Neural(g) => g.get_speed(),
+            MixedGenome::Neat(g) => g.get_speed(),
+        }
+    }
+
+    fn get_eval(&self, num: u8) -> i32 {
+        match self {
+            MixedGenome::Basic(g) => g.get_eval(num),
+            MixedGenome::Neural(g) => g.get_eval(num),
+            MixedGenome::Neat(g) => g.get_eval(num),
+        }
+    }
+
+    fn genes(&self) -> Vec<f32> {
+        match self {
+            MixedGenome::Basic(g) => g.genes(),
+            MixedGenome::Neural(g) => g.genes(),
+            MixedGenome::Neat(g) => g.genes(),
+        }
+    }
+
+    fn choose_direction(&self, senses: &Senses) -> Option<[f32; 4]> {
+        match self {
+            MixedGenome::Basic(g) => g.choose_direction(senses),
+            MixedGenome::Neural(g) => g.choose_direction(senses),
+            MixedGenome::Neat(g) => g.choose_direction(senses),
+        }
+    }
+
+    fn prepare_breeding(parents: &[&Self], config: &SpeciesConfig, shared: &mut Option<Innovations>) {
+        if config.genome == GenomeKind::Neat {
+            NeatGenome::prepare_breeding(&Self::neat(parents), config, shared.get_or_insert_with(Innovations::default));
+        }
+    }
+
+    fn choose_parents<R: Rng>(parents: &[&Self], shared: &Option<Innovations>, rng: &mut R) -> (usize, usize) {
+        match shared {
+            Some(innovations) => NeatGenome::choose_parents(&Self::neat(parents), innovations, rng),
+            None => (rng.gen_range(0..parents.len()), rng.gen_range(0..parents.len())),
+        }
+    }
+
+    fn describe_shared(shared: &Option<Innovations>) -> Option<String> {
+        shared.as_ref().and_then(NeatGenome::describe_shared)
+    }
+}
@@ -0,0 +1,398 @@
+//!
+//! everything related to the genome whose network grows with evolution (NEAT)
+//!
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::SpeciesConfig;
+use crate::genome::{standard_normal, BasicGenome, Crossover, EatingType, Genome, Senses};
+use crate::neural::{inputs, INPUTS, OUTPUTS};
+use crate::schema::Schema;
+
+/// the first id of a hidden node, the inputs and outputs come before
+const FIRST_HIDDEN: u32 = (INPUTS + OUTPUTS) as u32;
+/// the innovation numbers of the connections every genome starts with, from every input to every output
+const FIRST_INNOVATION: u32 = (INPUTS * OUTPUTS) as u32;
+/// tries to find two nodes which are not connected yet
+const CONNECTION_TRIES: usize = 20;
+
+/// the rates of the structural mutations and the speciation, chosen per species
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Neat {
+    ///chance of every connection weight to change by a gaussian step of `weight_sigma`
+    pub weight_rate: f32,
+    pub weight_sigma: f32,
+    ///chance of a child to get a new connection, a new node in an existing connection and
+    ///a connection switched on or off
+    pub add_connection: f32,
+    pub add_node: f32,
+    pub toggle: f32,
+    ///weights of the excess and disjoint connections and of the mean weight difference in
+    ///the compatibility distance
+    pub excess: f32,
+    pub disjoint: f32,
+    pub weight: f32,
+    ///genomes closer than this to the representative of a species belong to it
+    pub threshold: f32,
+}
+
+impl Default for Neat {
+    fn default() -> Self {
+        Neat {
+            weight_rate: 0.8,
+            weight_sigma: 0.5,
+            add_connection: 0.05,
+            add_node: 0.03,
+            toggle: 0.01,
+            excess: 1.0,
+            disjoint: 1.0,
+            weight: 0.4,
+            //the distance is relative to the 44 connections every network starts with
+            threshold: 0.6,
+        }
+    }
+}
+
+/// a connection of the network, the same new connection gets the same innovation number
+/// in every genome of a species
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Connection {
+    from: u32,
+    to: u32,
+    weight: f32,
+    enabled: bool,
+    innovation: u32,
+}
+
+/// what the genomes of a species share: the innovations and the NEAT species
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Innovations {
+    ///the nodes of every connection found after the starting ones, by innovation number
+    connections: Vec<(u32, u32)>,
+    ///the innovation number of every connection split by a hidden node, the node id follows from the position
+    splits: Vec<u32>,
+    species: Vec<NeatSpecies>,
+    next_species: u32,
+}
+
+/// genomes of similar networks, which only breed among themselves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NeatSpecies {
+    id: u32,
+    ///the connections of the first member of the last generation
+    representative: Vec<Connection>,
+    ///the parents of the current generation which belong to it
+    #[serde(skip)]
+    members: Vec<usize>,
+    ///the sum of the shared fitness of the members, the mean energy of the members
+    #[serde(skip)]
+    fitness: f32,
+}
+
+impl Innovations {
+    fn innovation(&mut self, from: u32, to: u32) -> u32 {
+        if from < INPUTS as u32 && (INPUTS as u32..FIRST_HIDDEN).contains(&to) {
+            return from * OUTPUTS as u32 + to - INPUTS as u32;
+        }
+        let i = match self.connections.iter().position(|c| *c == (from, to)) {
+            Some(i) => i,
+            None => {
+                self.connections.push((from, to));
+                self.connections.len() - 1
+            }
+        };
+        FIRST_INNOVATION + i as u32
+    }
+
+    fn split(&mut self, innovation: u32) -> u32 {
+        let i = match self.splits.iter().position(|s| *s == innovation) {
+            Some(i) => i,
+            None => {
+                self.splits.push(innovation);
+                self.splits.len() - 1
+            }
+        };
+        FIRST_HIDDEN + i as u32
+    }
+}
+
+/// the genes of the basic genome for the body and a network which chooses the direction
+/// instead of the eval weights. the network starts with every input connected to every
+/// output and grows new nodes and connections, the children of a generation are bred within
+/// species of similar networks with a share of the children after their mean energy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeatGenome {
+    body: BasicGenome,
+    ///sorted by innovation number
+    connections: Vec<Connection>,
+}
+
+impl NeatGenome {
+    ///the value of `node`, `memo` holds the ones already calculated
+    fn value(&self, node: u32, x: &[f32; INPUTS], memo: &mut BTreeMap<u32, f32>) -> f32 {
+        if node < INPUTS as u32 {
+            return x[node as usize];
+        }
+        if let Some(v) = memo.get(&node) {
+            return *v;
+        }
+        //a cycle, which the mutations never build, ends here
+        memo.insert(node, 0.0);
+        let sum: f32 = self.connections.iter()
+            .filter(|c| c.enabled && c.to == node)
+            .map(|c| c.weight * self.value(c.from, x, memo))
+            .sum();
+        memo.insert(node, sum.tanh());
+        sum.tanh()
+    }
+
+    ///whether `to` can be reached from `from`, switched off connections count as they may come back
+    fn reaches(&self, from: u32, to: u32) -> bool {
+        let mut open = vec![from];
+        let mut seen = vec![];
+        while let Some(node) = open.pop() {
+            if node == to {
+                return true;
+            }
+            if !seen.contains(&node) {
+                seen.push(node);
+                open.extend(self.connections.iter().filter(|c| c.from == node).map(|c| c.to));
+            }
+        }
+        false
+    }
+
+    fn hidden_nodes(&self) -> Vec<u32> {
+        let mut nodes: Vec<u32> = self.connections.iter().flat_map(|c| [c.from, c.to]).filter(|n| *n >= FIRST_HIDDEN).collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+    }
+
+    fn insert(&mut self, connection: Connection) {
+        let at = self.connections.partition_point(|c| c.innovation < connection.innovation);
+        self.connections.insert(at, connection);
+    }
+
+    ///a connection between two nodes which are not connected, without building a cycle
+    fn add_connection<R: Rng>(&mut self, innovations: &mut Innovations, rng: &mut R) {
+        let hidden = self.hidden_nodes();
+        let sources: Vec<u32> = (0..INPUTS as u32).chain(hidden.iter().copied()).collect();
+        let targets: Vec<u32> = (INPUTS as u32..FIRST_HIDDEN).chain(hidden.iter().copied()).collect();
+        for _ in 0..CONNECTION_TRIES {
+            let from = sources[rng.gen_range(0..sources.len())];
+            let to = targets[rng.gen_range(0..targets.len())];
+            if from == to || self.connections.iter().any(|c| c.from == from && c.to == to) || self.reaches(to, from) {
+                continue;
+            }
+            let innovation = innovations.innovation(from, to);
+            self.insert(Connection { from, to, weight: rng.gen_range(-1.0..1.0), enabled: true, innovation });
+            return;
+        }
+    }
+
+    ///a node in an enabled connection, which is switched off for one into and one out of the node
+    fn add_node<R: Rng>(&mut self, innovations: &mut Innovations, rng: &mut R) {
+        let enabled: Vec<usize> = (0..self.connections.len()).filter(|i| self.connections[*i].enabled).collect();
+        if enabled.is_empty() {
+            return;
+        }
+        let split = self.connections[enabled[rng.gen_range(0..enabled.len())]];
+        let node = innovations.split(split.innovation);
+        //the same connection was split before and switched on again
+        if self.hidden_nodes().contains(&node) {
+            return;
+        }
+        for c in self.connections.iter_mut().filter(|c| c.innovation == split.innovation) {
+            c.enabled = false;
+        }
+        let into = innovations.innovation(split.from, node);
+        let out = innovations.innovation(node, split.to);
+        self.insert(Connection { from: split.from, to: node, weight: 1.0, enabled: true, innovation: into });
+        self.insert(Connection { from: node, to: split.to, weight: split.weight, enabled: true, innovation: out });
+    }
+}
+
+impl Genome for NeatGenome {
+    type Shared = Innovations;
+
+    fn new<R: Rng>(e: EatingType, schema: &Schema, config: &SpeciesConfig, rng: &mut R) -> Self {
+        let body = BasicGenome::new(e, schema, config, rng);
+        let connections = (0..FIRST_INNOVATION).map(|innovation| Connection {
+            from: innovation / OUTPUTS as u32,
+            to: INPUTS as u32 + innovation % OUTPUTS as u32,
+            weight: rng.gen_range(-1.0..1.0),
+            enabled: true,
+            innovation,
+        }).collect();
+        NeatGenome { body, connections }
+    }
+
+    fn mutate<R: Rng>(&mut self, chance: i32, schema: &Schema, config: &SpeciesConfig, shared: &mut Innovations, rng: &mut R) {
+        self.body.mutate(chance, schema, config, &mut (), rng);
+        let neat = &config.neat;
+        for c in &mut self.connections {
+            if rng.gen::<f32>() < neat.weight_rate {
+                c.weight += neat.weight_sigma * standard_normal(rng);
+            }
+        }
+        if rng.gen::<f32>() < neat.toggle {
+            let i = rng.gen_range(0..self.connections.len());
+            self.connections[i].enabled = !self.connections[i].enabled;
+        }
+        if rng.gen::<f32>() < neat.add_connection {
+            self.add_connection(shared, rng);
+        }
+        if rng.gen::<f32>() < neat.add_node {
+            self.add_node(shared, rng);
+        }
+    }
+
+    ///the connections of both parents with the same innovation come from either, the others
+    ///from the parent with more energy. a connection switched off in a parent stays off with
+    ///a chance of 75%
+    fn crossover<R: Rng>(&self, other: &Self, schema: &Schema, method: Crossover, rng: &mut R) -> Self {
+        let body = self.body.crossover(&other.body, schema, method, rng);
+        if method == Crossover::Clone {
+            return NeatGenome { body, connections: self.connections.clone() };
+        }
+        let (fitter, weaker) = if other.get_energy() > self.get_energy() { (other, self) } else { (self, other) };
+        let connections = fitter.connections.iter().map(|c| {
+            match weaker.connections.binary_search_by_key(&c.innovation, |w| w.innovation) {
+                Ok(i) => {
+                    let w = &weaker.connections[i];
+                    let mut child = if rng.gen_bool(0.5) { *c } else { *w };
+                    if !c.enabled || !w.enabled {
+                        child.enabled = rng.gen::<f32>() >= 0.75;
+                    }
+                    child
+                }
+                Err(_) => *c,
+            }
+        }).collect();
+        NeatGenome { body, connections }
+    }
+
+    fn to_string(&self, schema: &Schema) -> String {
+        let enabled = self.connections.iter().filter(|c| c.enabled).count();
+        format!("{} net: {} hidden, {}/{} connections", self.body.to_string(schema), self.hidden_nodes().len(), enabled, self.connections.len())
+    }
+
+    fn get_detection_range(&self) -> f32 {
+        self.body.get_detection_range()
+    }
+
+    fn increase_energy(&mut self, energy: f32) {
+        self.body.increase_energy(energy);
+    }
+
+    fn has_enough_energy(&self, schema: &Schema) -> bool {
+        self.body.has_enough_energy(schema)
+    }
+
+    fn get_energy(&self) -> f32 {
+        self.body.get_energy()
+    }
+
+    fn get_weight(&self) -> f32 {
+        self.body.get_weight()
+    }
+
+    fn get_power(&self) -> f32 {
+        self.body.get_power()
+    }
+
+    fn get_speed(&self) -> f32 {
+        self.body.get_speed()
+    }
+
+    fn get_eval(&self, num: u8) -> i32 {
+        self.body.get_eval(num)
+    }
+
+    fn genes(&self) -> Vec<f32> {
+        self.body.genes()
+    }
+
+    fn choose_direction(&self, senses: &Senses) -> Option<[f32; 4]> {
+        let x = inputs(senses);
+        let mut memo = BTreeMap::new();
+        Some(std::array::from_fn(|o| self.value((INPUTS + o) as u32, &x, &mut memo)))
+    }
+
+    ///sorts the parents into the species of the last generation, or into new ones if they are
+    ///too far from all of them. every species gets the mean energy of its members as fitness
+    fn prepare_breeding(parents: &[&Self], config: &SpeciesConfig, shared: &mut Innovations) {
+        for s in &mut shared.species {
+            s.members.clear();
+        }
+        for (i, p) in parents.iter().enumerate() {
+            match shared.species.iter_mut().find(|s| distance(&s.representative, &p.connections, &config.neat) < config.neat.threshold) {
+                Some(s) => s.members.push(i),
+                None => {
+                    shared.species.push(NeatSpecies { id: shared.next_species, representative: p.connections.clone(), members: vec![i], fitness: 0.0 });
+                    shared.next_species += 1;
+                }
+            }
+        }
+        shared.species.retain(|s| !s.members.is_empty());
+        for s in &mut shared.species {
+            s.representative = parents[s.members[0]].connections.clone();
+            //fitness sharing: the energy of every member divided by the size of its species
+            s.fitness = s.members.iter().map(|m| parents[*m].get_energy().max(0.0)).sum::<f32>() / s.members.len() as f32;
+        }
+    }
+
+    ///a species chosen after its fitness, then two of its members
+    fn choose_parents<R: Rng>(_parents: &[&Self], shared: &Innovations, rng: &mut R) -> (usize, usize) {
+        let total: f32 = shared.species.iter().map(|s| s.fitness).sum();
+        let species = if total > 0.0 {
+            let mut pick = rng.gen_range(0.0..total);
+            shared.species.iter().find(|s| {
+                pick -= s.fitness;
+                pick < 0.0
+            }).unwrap_or(&shared.species[shared.species.len() - 1])
+        } else {
+            &shared.species[rng.gen_range(0..shared.species.len())]
+        };
+        let m = &species.members;
+        (m[rng.gen_range(0..m.len())], m[rng.gen_range(0..m.len())])
+    }
+
+    fn describe_shared(shared: &Innovations) -> Option<String> {
+        let sizes: Vec<String> = shared.species.iter().map(|s| format!("{}: {}", s.id, s.members.len())).collect();
+        Some(format!("neat species {} innovations {} [{}]", shared.species.len(), FIRST_INNOVATION as usize + shared.connections.len(), sizes.join(", ")))
+    }
+}
+
+/// the compatibility distance of two networks: the excess and disjoint connections relative to
+/// the larger network (at least 20 connections) and the mean weight difference of the matching ones
+fn distance(a: &[Connection], b: &[Connection], neat: &Neat) -> f32 {
+    let (mut i, mut j) = (0, 0);
+    let (mut disjoint, mut matching, mut difference) = (0, 0, 0.0);
+    while i < a.len() && j < b.len() {
+        match a[i].innovation.cmp(&b[j].innovation) {
+            std::cmp::Ordering::Equal => {
+                matching += 1;
+                difference += (a[i].weight - b[j].weight).abs();
+                i += 1;
+                j += 1;
+            }
+            std::cmp::Ordering::Less => {
+                disjoint += 1;
+                i += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                disjoint += 1;
+                j += 1;
+            }
+        }
+    }
+    let excess = (a.len() - i) + (b.len() - j);
+    let n = a.len().max(b.len()).max(20) as f32;
+    let mean = if matching > 0 { difference / matching as f32 } else { 0.0 };
+    neat.excess * excess as f32 / n + neat.disjoint * disjoint as f32 / n + neat.weight * mean
+}
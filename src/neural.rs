@@ -0,0 +1,142 @@
+//!
+//! everything related to the genome with a neural network as brain
+//!
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::SpeciesConfig;
+use crate::genome::{BasicGenome, Crossover, EatingType, Genome, Senses};
+use crate::schema::{Gene, GeneConfig, GeneType, Schema};
+
+/// offset and closeness of the nearest plant, prey and predator, the energy and a bias
+pub const INPUTS: usize = 11;
+const HIDDEN: usize = 6;
+/// a score for left, right, up and down
+pub const OUTPUTS: usize = 4;
+/// the weights of the input to the hidden layer, then those of the hidden layer (with a bias)
+/// to the outputs
+const WEIGHTS: usize = INPUTS * HIDDEN + (HIDDEN + 1) * OUTPUTS;
+/// mutation step of the weights, smaller than that of the body genes
+const WEIGHT_SIGMA: f32 = 0.3;
+
+/// the genes of the basic genome for the body and the weights of a small feed-forward net,
+/// which chooses the direction instead of the eval weights. the weights are the last genes of
+/// the schema, so they mutate and cross over like all others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeuralGenome {
+    body: BasicGenome,
+}
+
+impl NeuralGenome {
+    fn weights(&self) -> &[f32] {
+        let values = self.body.values();
+        &values[values.len() - WEIGHTS..]
+    }
+}
+
+impl Genome for NeuralGenome {
+    type Shared = ();
+
+    ///the genes of the config followed by the weights `brain_0` to `brain_93`
+    fn schema(config: &[GeneConfig], _species: &SpeciesConfig) -> Result<Schema, String> {
+        let mut schema = Schema::new(config)?;
+        for i in 0..WEIGHTS {
+            let name = format!("brain_{}", i);
+            if schema.index(&name).is_some() {
+                return Err(format!("gene {} is already a weight of the neural genome", name));
+            }
+            let mut gene = Gene::new(&name, GeneType::Float, [-1.0, 1.0], None, 0.0);
+            gene.sigma = Some(WEIGHT_SIGMA);
+            schema.genes.push(gene);
+        }
+        Ok(schema)
+    }
+
+    fn new<R: Rng>(e: EatingType, schema: &Schema, config: &SpeciesConfig, rng: &mut R) -> Self {
+        NeuralGenome { body: BasicGenome::new(e, schema, config, rng) }
+    }
+
+    fn mutate<R: Rng>(&mut self, chance: i32, schema: &Schema, config: &SpeciesConfig, shared: &mut (), rng: &mut R) {
+        self.body.mutate(chance, schema, config, shared, rng);
+    }
+
+    fn crossover<R: Rng>(&self, other: &Self, schema: &Schema, method: Crossover, rng: &mut R) -> Self {
+        NeuralGenome { body: self.body.crossover(&other.body, schema, method, rng) }
+    }
+
+    ///the body only, the weights are in the structured output
+    fn to_string(&self, schema: &Schema) -> String {
+        self.body.describe(&schema.genes[..schema.len() - WEIGHTS])
+    }
+
+    fn get_detection_range(&self) -> f32 {
+        self.body.get_detection_range()
+    }
+
+    fn increase_energy(&mut self, energy: f32) {
+        self.body.increase_energy(energy);
+    }
+
+    fn has_enough_energy(&self, schema: &Schema) -> bool {
+        self.body.has_enough_energy(schema)
+    }
+
+    fn get_energy(&self) -> f32 {
+        self.body.get_energy()
+    }
+
+    fn get_weight(&self) -> f32 {
+        self.body.get_weight()
+    }
+
+    fn get_power(&self) -> f32 {
+        self.body.get_power()
+    }
+
+    fn get_speed(&self) -> f32 {
+        self.body.get_speed()
+    }
+
+    fn get_eval(&self, num: u8) -> i32 {
+        self.body.get_eval(num)
+    }
+
+    fn genes(&self) -> Vec<f32> {
+        self.body.genes()
+    }
+
+    fn choose_direction(&self, senses: &Senses) -> Option<[f32; 4]> {
+        let w = self.weights();
+        let x = inputs(senses);
+        let hidden: [f32; HIDDEN] = std::array::from_fn(|h| {
+            x.iter().zip(&w[h * INPUTS..(h + 1) * INPUTS]).map(|(x, w)| x * w).sum::<f32>().tanh()
+        });
+        Some(std::array::from_fn(|o| {
+            let start = INPUTS * HIDDEN + o * (HIDDEN + 1);
+            let sum: f32 = hidden.iter().zip(&w[start..start + HIDDEN]).map(|(h, w)| h * w).sum();
+            (sum + w[start + HIDDEN]).tanh()
+        }))
+    }
+}
+
+/// whether `gene` is one of the weights the schema adds
+pub fn is_weight(gene: &str) -> bool {
+    (0..WEIGHTS).any(|i| format!("brain_{}", i) == gene)
+}
+
+/// the senses as numbers of about -1 to 1, zero for what is not in sight, the last one is the bias
+pub fn inputs(senses: &Senses) -> [f32; INPUTS] {
+    let range = senses.range.max(1.0);
+    let mut x = [0.0; INPUTS];
+    for (i, seen) in [senses.plant, senses.prey, senses.predator].into_iter().enumerate() {
+        if let Some((dx, dy)) = seen {
+            x[3 * i] = dx as f32 / range;
+            x[3 * i + 1] = dy as f32 / range;
+            x[3 * i + 2] = 1.0 / (1.0 + (dx.abs() + dy.abs()) as f32);
+        }
+    }
+    x[9] = senses.energy / (1.0 + senses.energy.abs());
+    //bias
+    x[10] = 1.0;
+    x
+}
@@ -0,0 +1,183 @@
+//!
+//! everything related to machine readable simulation output
+//!
+use std::{fs::File, io::{BufWriter, Write}, path::{Path, PathBuf}};
+
+use clap::ValueEnum;
+use serde_json::Value;
+
+use crate::cli::OutputArgs;
+use crate::events::EventLog;
+use crate::genome::Genome;
+use crate::render::Renderer;
+use crate::simulation::BasicSimulation;
+use crate::stats::GeneStats;
+
+/// file formats for the structured output next to the text log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DataFormat {
+    ///one .csv file per kind of record
+    Csv,
+    ///one .jsonl file with a "record" field naming the kind
+    Jsonl,
+}
+
+/// one typed line of output
+pub enum Record<'a> {
+    ///size of a species after a simulation step
+    Step { epoch: u16, step: u16, species: &'a str, count: usize },
+    ///state of a species after the selection at the end of an epoch
+    Epoch { epoch: u16, species: &'a str, survivors: usize, average: f64, died_out: bool },
+    ///all genes of one creature, `stage` is "start" at the beginning of an epoch
+    ///and "survivor" after the selection
+    ///the genes of all species are in every record, those of other genomes are empty
+    Genome { epoch: u16, stage: &'a str, species: &'a str, id: u64, pos: (i32, i32), genes: Vec<(&'a str, Option<f32>)> },
+    ///statistics of one gene over a species at the beginning of an epoch
+    GeneStat { epoch: u16, species: &'a str, gene: &'a str, stats: &'a GeneStats },
+    ///the ancestry of one creature, written for every creature at the end of a run.
+    ///founders have no parents, living creatures no death
+    Lineage { id: u64, species: &'a str, parents: Option<(u64, u64)>, born: u16, died: Option<u16>, cause: Option<&'a str> },
+}
+
+impl Record<'_> {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Record::Step { .. } => "step",
+            Record::Epoch { .. } => "epoch",
+            Record::Genome { .. } => "genome",
+            Record::GeneStat { .. } => "gene_stat",
+            Record::Lineage { .. } => "lineage",
+        }
+    }
+
+    ///the named values of the record, always in the same order for one kind
+    pub fn fields(&self) -> Vec<(&str, Value)> {
+        match self {
+            Record::Step { epoch, step, species, count } => vec![
+                ("epoch", (*epoch).into()), ("step", (*step).into()), ("species", (*species).into()), ("count", (*count).into()),
+            ],
+            Record::Epoch { epoch, species, survivors, average, died_out } => vec![
+                ("epoch", (*epoch).into()), ("species", (*species).into()), ("survivors", (*survivors).into()),
+                ("average", (*average).into()), ("died_out", (*died_out).into()),
+            ],
+            Record::Genome { epoch, stage, species, id, pos, genes } => {
+                let mut fields: Vec<(&str, Value)> = vec![
+                    ("epoch", (*epoch).into()), ("stage", (*stage).into()), ("species", (*species).into()),
+                    ("id", (*id).into()), ("x", pos.0.into()), ("y", pos.1.into()),
+                ];
+                //going through the text keeps the short f32 digits instead of the f64 widening noise
+                fields.extend(genes.iter().map(|(name, v)| (*name, v.map_or(Value::Null, |v| Value::from(v.to_string().parse::<f64>().unwrap_or(f64::NAN))))));
+                fields
+            }
+            Record::GeneStat { epoch, species, gene, stats } => vec![
+                ("epoch", (*epoch).into()), ("species", (*species).into()), ("gene", (*gene).into()),
+                ("mean", stats.mean.into()), ("variance", stats.variance.into()), ("min", stats.min.into()),
+                ("max", stats.max.into()), ("median", stats.median.into()),
+            ],
+            Record::Lineage { id, species, parents, born, died, cause } => vec![
+                ("id", (*id).into()), ("species", (*species).into()),
+                ("parent_1", parents.map(|p| p.0).into()), ("parent_2", parents.map(|p| p.1).into()),
+                ("born", (*born).into()), ("died", (*died).into()), ("cause", (*cause).into()),
+            ],
+        }
+    }
+}
+
+/// where the records of a simulation go
+pub trait Sink {
+    fn write(&mut self, record: &Record);
+    ///called once after the last record
+    fn finish(&mut self);
+}
+
+/// opens the sink for a run whose text log is `<dir>/<name>.txt`.
+/// every record carries the seed, so the files of many runs can be concatenated
+pub fn open(format: DataFormat, dir: &Path, name: &str, seed: u64) -> Box<dyn Sink> {
+    match format {
+        DataFormat::Csv => Box::new(CsvSink { dir: dir.join(name), seed, files: vec![] }),
+        DataFormat::Jsonl => Box::new(JsonlSink {
+            seed,
+            file: BufWriter::new(File::create(dir.join(format!("{}.jsonl", name))).expect("file problem")),
+        }),
+    }
+}
+
+/// lets `sim` write the structured data, the event stream and the checkpoints of a run
+/// whose log is `<dir>/<name>.txt`
+pub fn attach<G: Genome>(sim: &mut BasicSimulation<G>, dir: &Path, name: &str, files: &OutputArgs) {
+    if let Some(format) = files.data {
+        let seed = sim.get_seed();
+        sim.set_sink(open(format, dir, name, seed));
+    }
+    if files.events {
+        let log = EventLog::create(&dir.join(format!("{}.events.jsonl", name)), sim.get_world(), sim.species_names());
+        sim.set_events(log);
+    }
+    if files.png || files.gif {
+        let renderer = Renderer::new(sim.get_config(), &sim.species_gene_names(), dir.join(name), files.png, files.gif).expect("render problem");
+        sim.set_renderer(renderer);
+    }
+    if !files.checkpoints.is_empty() {
+        let last = sim.get_epochs();
+        assert!(files.checkpoints.iter().all(|c| *c < last), "checkpoints have to be before the last epoch {}", last);
+        sim.set_checkpoints(files.checkpoints.clone(), dir.join(name));
+    }
+}
+
+/// writes `<name>.<kind>s.csv`, the header is taken from the first record of a kind
+struct CsvSink {
+    dir: PathBuf,
+    seed: u64,
+    files: Vec<(&'static str, BufWriter<File>)>,
+}
+
+impl Sink for CsvSink {
+    fn write(&mut self, record: &Record) {
+        let fields = record.fields();
+        let kind = record.kind();
+        let i = match self.files.iter().position(|(k, _)| *k == kind) {
+            Some(i) => i,
+            None => {
+                let path = format!("{}.{}s.csv", self.dir.display(), kind);
+                let mut file = BufWriter::new(File::create(path).expect("file problem"));
+                let header: Vec<&str> = fields.iter().map(|(name, _)| *name).collect();
+                writeln!(file, "seed,{}", header.join(",")).expect("write went wrong");
+                self.files.push((kind, file));
+                self.files.len() - 1
+            }
+        };
+        let values: Vec<String> = fields.iter().map(|(_, v)| match v {
+            Value::String(s) if s.contains([',', '"', '\n']) => format!("\"{}\"", s.replace('"', "\"\"")),
+            Value::String(s) => s.clone(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        }).collect();
+        writeln!(self.files[i].1, "{},{}", self.seed, values.join(",")).expect("write went wrong");
+    }
+
+    fn finish(&mut self) {
+        for (_, file) in self.files.iter_mut() {
+            file.flush().expect("write went wrong");
+        }
+    }
+}
+
+/// writes every record as one json object per line
+struct JsonlSink {
+    seed: u64,
+    file: BufWriter<File>,
+}
+
+impl Sink for JsonlSink {
+    fn write(&mut self, record: &Record) {
+        let mut line = format!("{{\"record\":\"{}\",\"seed\":{}", record.kind(), self.seed);
+        for (name, value) in record.fields() {
+            line.push_str(&format!(",\"{}\":{}", name, value));
+        }
+        writeln!(self.file, "{}}}", line).expect("write went wrong");
+    }
+
+    fn finish(&mut self) {
+        self.file.flush().expect("write went wrong");
+    }
+}
@@ -0,0 +1,299 @@
+//!
+//! everything related to drawing charts of written simulation data
+//!
+use std::{collections::BTreeMap, fmt::Write, fs, path::Path};
+
+use crate::data::{batch_species, read_batch, read_records};
+use crate::genome::GENES;
+use crate::neural;
+use crate::render::PALETTE;
+use crate::stats::{kaplan_meier, mean_sd};
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 360.0;
+/// space for the title, the axes and their labels
+const MARGIN: (f64, f64, f64, f64) = (60.0, 20.0, 40.0, 50.0); //left right top bottom
+/// weight, speed, power and detection, the first built in genes
+const BODY: usize = 4;
+
+/// one line of a chart, optionally with a band around it
+pub struct Series {
+    pub name: String,
+    pub color: String,
+    pub points: Vec<(f64, f64)>,
+    ///lower and upper value at every point
+    pub band: Option<Vec<(f64, f64)>>,
+}
+
+/// the color of the species with index `i`, like in the images
+pub fn color(i: usize) -> String {
+    let c = PALETTE[i % PALETTE.len()];
+    format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+/// draws the charts of a run written with `--data`: `<run>.counts.svg` with the size of every
+/// species per step and `<run>.genes.svg` with mean ± sd of the body genes and the genes of
+/// the config at the start of each epoch
+pub fn plot_run(run: &Path, out: &Path) -> Result<(), String> {
+    let name = run.file_name().and_then(|n| n.to_str()).ok_or("the run needs a name")?;
+    let steps = read_records(run, "step")?;
+    let mut counts: BTreeMap<String, Vec<(f64, f64)>> = BTreeMap::new();
+    let mut order: Vec<String> = vec![];
+    let steps_per_epoch = steps.iter().filter_map(|r| r.get("step")?.parse::<f64>().ok()).fold(0.0, f64::max);
+    for r in &steps {
+        let (epoch, step, count) = (number(r, "epoch")?, number(r, "step")?, number(r, "count")?);
+        let species = r.get("species").ok_or("step record without species")?;
+        if !order.contains(species) {
+            order.push(species.clone());
+        }
+        counts.entry(species.clone()).or_default().push(((epoch - 1.0) * steps_per_epoch + step, count));
+    }
+    let series: Vec<Series> = order.iter().enumerate().map(|(i, s)| Series {
+        name: s.clone(), color: color(i), points: counts.remove(s).unwrap_or_default(), band: None,
+    }).collect();
+    let epochs: Vec<f64> = (1..).map(|e| e as f64 * steps_per_epoch).take_while(|x| series.iter().any(|s| s.points.last().is_some_and(|p| p.0 > *x))).collect();
+    let chart = line_chart(&format!("population of {}", name), "simulation step", "members", &series, &epochs);
+    write(&out.join(format!("{}.counts.svg", name)), &document(WIDTH, HEIGHT, &chart))?;
+
+    //mean and standard deviation of the drawn genes per species and epoch
+    let genomes = read_records(run, "genome")?;
+    let mut genes: Vec<String> = vec![];
+    let mut values: BTreeMap<(String, String, u32), Vec<f64>> = BTreeMap::new();
+    let fixed = ["seed", "epoch", "stage", "species", "id", "x", "y"];
+    for r in genomes.iter().filter(|r| r.get("stage").is_some_and(|s| s == "start")) {
+        let (epoch, species) = (number(r, "epoch")? as u32, r.get("species").ok_or("genome record without species")?);
+        //the genes of the genomes of other species are empty
+        for (gene, value) in r.iter().filter(|(k, v)| !fixed.contains(&k.as_str()) && drawn(k) && !v.is_empty()) {
+            if !genes.contains(gene) {
+                genes.push(gene.clone());
+            }
+            let value: f64 = value.parse().map_err(|_| format!("gene {} is not a number", gene))?;
+            values.entry((gene.clone(), species.clone(), epoch)).or_default().push(value);
+        }
+    }
+    genes.sort_by_key(|g| genes_order(g));
+    let charts: Vec<String> = genes.iter().map(|gene| {
+        let series: Vec<Series> = order.iter().enumerate().map(|(i, s)| {
+            let stats: Vec<(f64, f64, f64)> = values.range((gene.clone(), s.clone(), 0)..=(gene.clone(), s.clone(), u32::MAX))
+                .map(|((_, _, e), v)| {
+                    let (mean, sd) = mean_sd(v);
+                    (*e as f64, mean, sd)
+                })
+                .collect();
+            Series {
+                name: s.clone(),
+                color: color(i),
+                points: stats.iter().map(|(e, m, _)| (*e, *m)).collect(),
+                band: Some(stats.iter().map(|(_, m, sd)| (m - sd, m + sd)).collect()),
+            }
+        }).collect();
+        line_chart(&format!("{} (mean ± sd)", gene), "epoch", gene, &series, &[])
+    }).collect();
+    if !charts.is_empty() {
+        write(&out.join(format!("{}.genes.svg", name)), &grid(&charts, 2))?;
+    }
+    Ok(())
+}
+
+/// the body genes and those added in the config, the evals, the mutation rate and the
+/// weights of the neural genome would only crowd the chart
+fn drawn(gene: &str) -> bool {
+    GENES[..BODY].contains(&gene) || !GENES.contains(&gene) && gene != "mutation_rate" && !neural::is_weight(gene)
+}
+
+/// the body genes come first in their usual order, the genes of the config after them
+fn genes_order(gene: &str) -> usize {
+    GENES[..BODY].iter().position(|g| *g == gene).unwrap_or(BODY)
+}
+
+/// draws `<res>.dieout.svg`, how many runs of a batch lost which species in which epoch,
+/// and `<res>.survival.svg`, the kaplan-meier survival of every species over the epochs
+pub fn plot_batch(res: &Path, out: &Path) -> Result<(), String> {
+    let batch = read_batch(res)?;
+    let species = batch_species(batch.iter());
+    let runs = batch.len();
+    let last = batch.iter().map(|r| r.stopped).max().unwrap_or(0);
+    //every species with the epochs of the runs, and whether it died out in them
+    let times: Vec<Vec<(u16, bool)>> = species.iter().map(|s| batch.iter().map(|r| match r.extinction(s) {
+        Some(e) => (e, true),
+        None => (r.stopped, false),
+    }).collect()).collect();
+    let stem = res.file_stem().and_then(|n| n.to_str()).unwrap_or("res");
+
+    let bars: Vec<(String, Vec<f64>)> = species.iter().zip(&times)
+        .map(|(n, t)| (n.clone(), (1..=last).map(|e| t.iter().filter(|r| **r == (e, true)).count() as f64).collect()))
+        .collect();
+    let total: usize = times.iter().flatten().filter(|r| r.1).count();
+    let title = format!("{} die outs in {} runs", total, runs);
+    write(&out.join(format!("{}.dieout.svg", stem)), &document(WIDTH, HEIGHT, &bar_chart(&title, "epoch of the die out", "runs", &bars)))?;
+
+    let series: Vec<Series> = species.iter().zip(&times).enumerate().map(|(i, (n, t))| {
+        //drawn as steps, the survival holds until the end of an epoch
+        let curve = kaplan_meier(t, last);
+        let mut points = vec![(0.0, 1.0)];
+        for (e, s) in curve.iter().enumerate() {
+            points.extend([(e as f64 + 1.0, points[points.len() - 1].1), (e as f64 + 1.0, *s)]);
+        }
+        Series { name: n.clone(), color: color(i), points, band: None }
+    }).collect();
+    let chart = line_chart(&format!("survival over {} runs (kaplan-meier)", runs), "epoch", "share of runs with the species", &series, &[]);
+    write(&out.join(format!("{}.survival.svg", stem)), &document(WIDTH, HEIGHT, &chart))
+}
+
+fn number(record: &BTreeMap<String, String>, key: &str) -> Result<f64, String> {
+    record.get(key).and_then(|v| v.parse().ok()).ok_or_else(|| format!("record without a number {}", key))
+}
+
+fn write(path: &Path, svg: &str) -> Result<(), String> {
+    fs::write(path, svg).map_err(|e| format!("could not write {}: {}", path.display(), e))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+fn document(width: f64, height: f64, content: &str) -> String {
+    format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"12\">\n<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n{c}</svg>\n", w = width, h = height, c = content)
+}
+
+/// charts side by side, `cols` per row
+fn grid(charts: &[String], cols: usize) -> String {
+    let rows = charts.len().div_ceil(cols);
+    let mut content = String::new();
+    for (i, chart) in charts.iter().enumerate() {
+        let (x, y) = ((i % cols) as f64 * WIDTH, (i / cols) as f64 * HEIGHT);
+        let _ = writeln!(content, "<svg x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\">\n{}</svg>", x, y, WIDTH, HEIGHT, chart);
+    }
+    document(WIDTH * cols.min(charts.len()) as f64, HEIGHT * rows as f64, &content)
+}
+
+/// a step of 1, 2 or 5 times a power of ten giving about five ticks
+fn tick_step(range: f64) -> f64 {
+    let rough = (range / 5.0).max(f64::MIN_POSITIVE);
+    let power = 10f64.powf(rough.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].into_iter().map(|m| m * power).find(|s| *s >= rough).unwrap_or(10.0 * power)
+}
+
+/// the frame, ticks, labels and title of a chart over `x` and `y` and the mapping into it
+fn axes(out: &mut String, title: &str, x_label: &str, y_label: &str, x: (f64, f64), y: (f64, f64)) -> impl Fn(f64, f64) -> (f64, f64) {
+    let (l, r, t, b) = MARGIN;
+    let (pw, ph) = (WIDTH - l - r, HEIGHT - t - b);
+    let sx = if x.1 > x.0 { pw / (x.1 - x.0) } else { 0.0 };
+    let sy = if y.1 > y.0 { ph / (y.1 - y.0) } else { 0.0 };
+    let map = move |vx: f64, vy: f64| (l + (vx - x.0) * sx, t + ph - (vy - y.0) * sy);
+    let _ = writeln!(out, "<text x=\"{}\" y=\"20\" text-anchor=\"middle\" font-size=\"14\">{}</text>", WIDTH / 2.0, escape(title));
+    let _ = writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"#444\"/>", l, t, pw, ph);
+    for (min, max, vertical) in [(x.0, x.1, true), (y.0, y.1, false)] {
+        let step = tick_step(max - min);
+        let mut v = (min / step).ceil() * step;
+        while v <= max + step * 1e-9 {
+            let (px, py) = if vertical { map(v, y.0) } else { map(x.0, v) };
+            let label = format_tick(v, step);
+            if vertical {
+                let _ = writeln!(out, "<line x1=\"{px:.1}\" y1=\"{py:.1}\" x2=\"{px:.1}\" y2=\"{:.1}\" stroke=\"#444\"/><text x=\"{px:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{label}</text>", py + 5.0, py + 18.0);
+            } else {
+                let _ = writeln!(out, "<line x1=\"{:.1}\" y1=\"{py:.1}\" x2=\"{px:.1}\" y2=\"{py:.1}\" stroke=\"#444\"/><text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{label}</text>", px - 5.0, px - 8.0, py + 4.0);
+            }
+            v += step;
+        }
+    }
+    let _ = writeln!(out, "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>", l + pw / 2.0, HEIGHT - 10.0, escape(x_label));
+    let _ = writeln!(out, "<text transform=\"translate(15 {}) rotate(-90)\" text-anchor=\"middle\">{}</text>", t + ph / 2.0, escape(y_label));
+    map
+}
+
+fn format_tick(v: f64, step: f64) -> String {
+    //rounding errors would show as -0
+    let v = if v.abs() < step * 1e-9 { 0.0 } else { v };
+    if step >= 1.0 { format!("{}", v.round()) } else { format!("{:.*}", (-step.log10().floor()) as usize, v) }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// the legend in the top right corner
+fn legend(out: &mut String, entries: &[(&str, &str)]) {
+    for (i, (name, color)) in entries.iter().enumerate() {
+        let y = MARGIN.2 + 15.0 + i as f64 * 16.0;
+        let x = WIDTH - MARGIN.1 - 120.0;
+        let _ = writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"10\" height=\"10\" fill=\"{}\"/><text x=\"{}\" y=\"{}\">{}</text>", x, y - 9.0, color, x + 14.0, y, escape(name));
+    }
+}
+
+/// lines with optional bands, `marks` are x values drawn as dashed vertical lines
+fn line_chart(title: &str, x_label: &str, y_label: &str, series: &[Series], marks: &[f64]) -> String {
+    let mut out = String::new();
+    let xs = series.iter().flat_map(|s| s.points.iter().map(|p| p.0));
+    let ys = series.iter().flat_map(|s| {
+        s.points.iter().map(|p| p.1).chain(s.band.iter().flatten().flat_map(|b| [b.0, b.1]))
+    });
+    let x = bounds(xs, false);
+    let y = bounds(ys, true);
+    let map = axes(&mut out, title, x_label, y_label, x, y);
+    for m in marks {
+        let (a, b) = (map(*m, y.0), map(*m, y.1));
+        let _ = writeln!(out, "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#bbb\" stroke-dasharray=\"4 4\"/>", a.0, a.1, b.0, b.1);
+    }
+    for s in series {
+        if let Some(band) = &s.band {
+            let upper = s.points.iter().zip(band).map(|(p, b)| map(p.0, b.1));
+            let lower = s.points.iter().zip(band).rev().map(|(p, b)| map(p.0, b.0));
+            let points: Vec<String> = upper.chain(lower).map(|(px, py)| format!("{:.1},{:.1}", px, py)).collect();
+            let _ = writeln!(out, "<polygon points=\"{}\" fill=\"{}\" fill-opacity=\"0.2\" stroke=\"none\"/>", points.join(" "), s.color);
+        }
+        let points: Vec<String> = s.points.iter().map(|p| map(p.0, p.1)).map(|(px, py)| format!("{:.1},{:.1}", px, py)).collect();
+        let _ = writeln!(out, "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\"/>", points.join(" "), s.color);
+    }
+    let entries: Vec<(&str, &str)> = series.iter().map(|s| (s.name.as_str(), s.color.as_str())).collect();
+    legend(&mut out, &entries);
+    out
+}
+
+/// groups of bars at x = 1, 2, ..., one bar per group entry and value
+fn bar_chart(title: &str, x_label: &str, y_label: &str, bars: &[(String, Vec<f64>)]) -> String {
+    let mut out = String::new();
+    let n = bars.iter().map(|b| b.1.len()).max().unwrap_or(0);
+    let top = bars.iter().flat_map(|b| b.1.iter().cloned()).fold(1.0, f64::max);
+    let map = axes(&mut out, title, x_label, y_label, (0.5, n as f64 + 0.5), (0.0, top));
+    let width = 0.8 / bars.len().max(1) as f64;
+    for (i, (_, values)) in bars.iter().enumerate() {
+        for (j, v) in values.iter().enumerate().filter(|(_, v)| **v > 0.0) {
+            let x = j as f64 + 1.0 - 0.4 + i as f64 * width;
+            let (a, b) = (map(x, *v), map(x + width, 0.0));
+            let _ = writeln!(out, "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>", a.0, a.1, b.0 - a.0, b.1 - a.1, color(i));
+        }
+    }
+    let colors: Vec<String> = (0..bars.len()).map(color).collect();
+    let entries: Vec<(&str, &str)> = bars.iter().zip(&colors).map(|(b, c)| (b.0.as_str(), c.as_str())).collect();
+    legend(&mut out, &entries);
+    out
+}
+
+/// the range of the values, counts start at zero
+fn bounds(values: impl Iterator<Item = f64>, from_zero: bool) -> (f64, f64) {
+    let (mut min, mut max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), v| (a.min(v), b.max(v)));
+    if !min.is_finite() {
+        return (0.0, 1.0);
+    }
+    if from_zero && min >= 0.0 {
+        min = 0.0;
+    }
+    if max <= min {
+        max = min + 1.0;
+    }
+    (min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_body_and_config_genes_are_drawn() {
+        for gene in ["weight", "speed", "power", "detection", "stamina", "brain"] {
+            assert!(drawn(gene), "{}", gene);
+        }
+        for gene in ["eval_1", "eval_3", "mutation_rate", "brain_0", "brain_93"] {
+            assert!(!drawn(gene), "{}", gene);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//!
+//! everything related to drawing the world into images
+//!
+use std::{borrow::Cow, fs::{self, File}, io::BufWriter, path::PathBuf};
+
+use crate::config::{parse_color, SimulationConfig};
+use crate::genome::Genome;
+use crate::world::World;
+
+/// colors of the species without a color in the config, in the order of the config
+pub const PALETTE: [[u8; 3]; 6] = [[255, 214, 0], [213, 0, 0], [0, 184, 212], [170, 0, 255], [41, 98, 255], [238, 238, 238]];
+/// shades of every species color, used for the tint
+const SHADES: usize = 16;
+const BACKGROUND: u8 = 0;
+const PLANT: u8 = 1;
+
+/// writes the frames of a run as png files and gif animations.
+/// the images use one palette: background, plants and the shades of every species
+pub struct Renderer {
+    cell: u32,
+    delay: u16,
+    palette: Vec<u8>,
+    ///per species the index of the tinting gene with its min and max
+    tints: Vec<Option<(usize, f32, f32)>>,
+    ///`<dir>/<name>`, the files are named after it
+    prefix: PathBuf,
+    png: bool,
+    gif: bool,
+    animation: Option<gif::Encoder<BufWriter<File>>>,
+}
+
+impl Renderer {
+    /// `genes` are the gene names of the genome of every species
+    pub fn new(config: &SimulationConfig, genes: &[Vec<&str>], prefix: PathBuf, png: bool, gif: bool) -> Result<Renderer, String> {
+        let render = &config.render;
+        let mut palette: Vec<u8> = vec![];
+        palette.extend(parse_color(&render.background)?);
+        palette.extend(parse_color(&render.plant_color)?);
+        let mut tints = vec![];
+        for (i, s) in config.species.iter().enumerate() {
+            let color = match render.colors.get(&s.name) {
+                Some(c) => parse_color(c)?,
+                None => PALETTE[i % PALETTE.len()],
+            };
+            for shade in 0..SHADES {
+                let brightness = 0.25 + 0.75 * shade as f32 / (SHADES - 1) as f32;
+                palette.extend(color.map(|c| (c as f32 * brightness).round() as u8));
+            }
+            let tint = match render.tint.iter().find(|t| t.species == s.name) {
+                Some(t) => {
+                    let genes = &genes[i];
+                    let gene = genes.iter().position(|g| *g == t.gene)
+                        .ok_or_else(|| format!("tint of {} uses unknown gene {}, known are: {}", s.name, t.gene, genes.join(", ")))?;
+                    Some((gene, t.min, t.max))
+                }
+                None => None,
+            };
+            tints.push(tint);
+        }
+        if palette.len() > 256 * 3 {
+            return Err(format!("too many species to draw, at most {}", (256 - 2) / SHADES));
+        }
+        if gif && (config.world.width as u32 * render.cell_size > u16::MAX as u32 || config.world.height as u32 * render.cell_size > u16::MAX as u32) {
+            return Err(String::from("the world is too large for a gif, use a smaller cell_size"));
+        }
+        if png {
+            fs::create_dir_all(format!("{}.frames", prefix.display())).map_err(|e| e.to_string())?;
+        }
+        Ok(Renderer { cell: render.cell_size, delay: render.frame_delay, palette, tints, prefix, png, gif, animation: None })
+    }
+
+    /// draws one frame: plants first, then every species on top of the ones before it.
+    /// only the cells inside the bounds of the world are drawn
+    pub fn capture<'a, G: Genome + 'a>(
+        &mut self, epoch: u16, step: u16, world: &World,
+        plants: impl Iterator<Item = &'a (i32, i32)>,
+        creatures: impl Iterator<Item = (usize, &'a (i32, i32), &'a G)>,
+    ) {
+        let (w, h) = (world.width as u32, world.height as u32);
+        let mut cells = vec![BACKGROUND; (w * h) as usize];
+        let min = world.min();
+        let index = |pos: &(i32, i32)| world.contains(pos).then(|| ((pos.1 - min.1) as u32 * w + (pos.0 - min.0) as u32) as usize);
+        for i in plants.filter_map(index) {
+            cells[i] = PLANT;
+        }
+        let mut creatures: Vec<(usize, &(i32, i32), &G)> = creatures.collect();
+        creatures.sort_by_key(|c| c.0);
+        for (s, pos, genome) in creatures {
+            let Some(i) = index(pos) else { continue };
+            let shade = match self.tints[s] {
+                Some((gene, lo, hi)) => {
+                    let value = genome.genes()[gene];
+                    (((value - lo) / (hi - lo)).clamp(0.0, 1.0) * (SHADES - 1) as f32).round() as usize
+                }
+                None => SHADES - 1,
+            };
+            cells[i] = (2 + s * SHADES + shade) as u8;
+        }
+        //every cell becomes a square of cell x cell pixels
+        let (pw, ph) = (w * self.cell, h * self.cell);
+        let mut pixels = Vec::with_capacity((pw * ph) as usize);
+        for row in cells.chunks(w as usize) {
+            let line: Vec<u8> = row.iter().flat_map(|c| std::iter::repeat_n(*c, self.cell as usize)).collect();
+            for _ in 0..self.cell {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        if self.png {
+            self.write_png(epoch, step, pw, ph, &pixels);
+        }
+        if self.gif {
+            self.add_gif_frame(epoch, pw, ph, pixels);
+        }
+    }
+
+    fn write_png(&self, epoch: u16, step: u16, width: u32, height: u32, pixels: &[u8]) {
+        let path = format!("{}.frames/e{:03}_s{:03}.png", self.prefix.display(), epoch, step);
+        let file = BufWriter::new(File::create(path).expect("file problem"));
+        let mut encoder = png::Encoder::new(file, width, height);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(self.palette.as_slice());
+        let mut writer = encoder.write_header().expect("png problem");
+        writer.write_image_data(pixels).expect("png problem");
+    }
+
+    ///the first frame of an epoch starts `<prefix>.epoch<n>.gif`
+    fn add_gif_frame(&mut self, epoch: u16, width: u32, height: u32, pixels: Vec<u8>) {
+        if self.animation.is_none() {
+            let file = BufWriter::new(File::create(format!("{}.epoch{}.gif", self.prefix.display(), epoch)).expect("file problem"));
+            let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &self.palette).expect("gif problem");
+            encoder.set_repeat(gif::Repeat::Infinite).expect("gif problem");
+            self.animation = Some(encoder);
+        }
+        let frame = gif::Frame {
+            width: width as u16,
+            height: height as u16,
+            delay: self.delay,
+            buffer: Cow::Owned(pixels),
+            ..gif::Frame::default()
+        };
+        self.animation.as_mut().expect("no animation").write_frame(&frame).expect("gif problem");
+    }
+
+    ///finishes the animation of the current epoch
+    pub fn end_epoch(&mut self) {
+        self.animation = None;
+    }
+}
@@ -0,0 +1,125 @@
+//!
+//! everything related to the genes a genome is made of
+//!
+use serde::{Deserialize, Serialize};
+
+use crate::genome::{Bound, GENES};
+
+/// whether a gene holds any number or only whole ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeneType {
+    #[default]
+    Float,
+    Int,
+}
+
+/// a gene as written in the config, missing values are taken from the built in gene
+/// of the same name or from the defaults of new genes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneConfig {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: Option<GeneType>,
+    pub init: Option<[f32; 2]>,
+    pub bounds: Option<Bound>,
+    pub sigma: Option<f32>,
+    pub rate: Option<f32>,
+    pub cost: Option<f32>,
+}
+
+/// one gene of the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gene {
+    pub name: String,
+    pub kind: GeneType,
+    ///the first generation draws the gene from the first (inclusive) to the second (exclusive)
+    pub init: [f32; 2],
+    ///the range the gene is kept in, unless the mutation of a species sets another
+    pub bounds: Bound,
+    ///size of a mutation step, the sigma or eval_sigma of the species if missing
+    pub sigma: Option<f32>,
+    ///chance to mutate with `per_gene`, the mutation chance if missing
+    pub rate: Option<f32>,
+    ///energy a creature needs at the end of an epoch per unit of the gene
+    pub cost: f32,
+}
+
+impl Gene {
+    pub fn new(name: &str, kind: GeneType, init: [f32; 2], min: Option<f32>, cost: f32) -> Gene {
+        Gene { name: name.to_owned(), kind, init, bounds: Bound { min, max: None, reflect: false }, sigma: None, rate: None, cost }
+    }
+}
+
+/// the genes of a basic genome: weight, speed, power, detection and the three eval weights
+/// always come first, the genes added in the config follow in their order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    pub genes: Vec<Gene>,
+}
+
+impl Schema {
+    /// the built in genes changed and extended by `config`
+    pub fn new(config: &[GeneConfig]) -> Result<Schema, String> {
+        let mut genes = vec![
+            Gene::new(GENES[0], GeneType::Float, [0.1, 5.0], Some(0.1), 0.2),
+            Gene::new(GENES[1], GeneType::Float, [0.0, 5.0], Some(0.0), 0.2),
+            Gene::new(GENES[2], GeneType::Float, [0.0, 5.0], Some(0.0), 0.2),
+            Gene::new(GENES[3], GeneType::Float, [0.0, 5.0], Some(0.0), 0.2),
+            Gene::new(GENES[4], GeneType::Int, [-1000.0, 1000.0], None, 0.0),
+            Gene::new(GENES[5], GeneType::Int, [-1000.0, 1000.0], None, 0.0),
+            Gene::new(GENES[6], GeneType::Int, [-1000.0, 1000.0], None, 0.0),
+        ];
+        for c in config {
+            if c.name.is_empty() || c.name == "mutation_rate" {
+                return Err(format!("a gene needs a name other than \"{}\"", c.name));
+            }
+            let gene = match genes.iter().position(|g| g.name == c.name) {
+                Some(i) if i >= GENES.len() => return Err(format!("gene {} is declared twice", c.name)),
+                Some(i) => {
+                    if c.kind.is_some_and(|k| k != genes[i].kind) {
+                        return Err(format!("the type of the built in gene {} can not change", c.name));
+                    }
+                    &mut genes[i]
+                }
+                None => {
+                    genes.push(Gene::new(&c.name, GeneType::Float, [0.0, 1.0], None, 0.0));
+                    genes.last_mut().expect("no gene")
+                }
+            };
+            gene.kind = c.kind.unwrap_or(gene.kind);
+            gene.init = c.init.unwrap_or(gene.init);
+            gene.bounds = c.bounds.unwrap_or(gene.bounds);
+            gene.sigma = c.sigma.or(gene.sigma);
+            gene.rate = c.rate.or(gene.rate);
+            gene.cost = c.cost.unwrap_or(gene.cost);
+            let whole = gene.kind == GeneType::Float || (gene.init[0] as i32) < gene.init[1] as i32;
+            if !(gene.init[0] < gene.init[1] && gene.init.iter().all(|v| v.is_finite()) && whole) {
+                return Err(format!("the init range of gene {} needs a first value below the second, for int genes in whole numbers", gene.name));
+            }
+            if gene.sigma.is_some_and(|s| !(s > 0.0 && s.is_finite())) || gene.rate.is_some_and(|r| !(0.0..=1.0).contains(&r)) {
+                return Err(format!("gene {} needs a sigma above 0 and a rate between 0 and 1", gene.name));
+            }
+            if let (Some(min), Some(max)) = (gene.bounds.min, gene.bounds.max) {
+                if min > max {
+                    return Err(format!("the bounds of gene {} need min <= max", gene.name));
+                }
+            }
+        }
+        Ok(Schema { genes })
+    }
+
+    pub fn len(&self) -> usize {
+        self.genes.len()
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.genes.iter().position(|g| g.name == name)
+    }
+
+    /// the names of the genes followed by "mutation_rate", the names of `Genome::genes`
+    pub fn names(&self) -> Vec<&str> {
+        self.genes.iter().map(|g| g.name.as_str()).chain(["mutation_rate"]).collect()
+    }
+}
@@ -0,0 +1,1061 @@
+//!
+//! everything related to running the ecosystem
+//!
+use core::time;
+use std::{fs::{self, File}, io::{stdout, Write}, path::{Path, PathBuf}, thread};
+
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{SimulationConfig, SpeciesConfig, PLANTS};
+use crate::events::{Event, EventLog};
+use crate::genealogy::{Creature, DeathCause, Genealogy};
+use crate::genome::{EatingType, Genome, Senses};
+use crate::output::{Record, Sink};
+use crate::render::Renderer;
+use crate::schema::Schema;
+use crate::spatial::SpatialMap;
+use crate::stats::SpeciesStats;
+use crate::world::{Metric, Part, Topology, World};
+
+const MILLIS_PER_FRAME: u64 = 1000; //in milliseconds
+/// format of the checkpoint files, raised whenever their content changes
+const CHECKPOINT_VERSION: u32 = 7;
+/// brings the scores of a brain to the scale of the eval weights
+const BRAIN_SCALE: f32 = 1000.0;
+
+pub trait Simulation{
+    fn new(epochs: u16, sim_time: u16, mutation_chance: i32, config: SimulationConfig, seed: u64, file: File) -> Self;
+    fn run(&mut self);
+}
+
+///for evaluation purposes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationResult{
+    pub epoch: u16,
+    ///the vectors follow the species order of the config
+    average: Vec<i32>,
+    ///the first species that died out
+    pub die_out: Option<String>,
+    ///the epoch every species died out at
+    extinct: Vec<Option<u16>>,
+    ///the genes of every species at the start of every epoch
+    history: Vec<Vec<SpeciesStats>>,
+}
+impl SimulationResult {
+    fn new(n: usize) -> SimulationResult{
+        SimulationResult{
+            epoch: 0,
+            average: vec![0; n],
+            die_out: None,
+            extinct: vec![None; n],
+            history: vec![],
+        }
+    }
+
+    pub fn get_average(&self, species: usize) -> i32 {
+        self.average[species] / self.epoch as i32
+    }
+
+    pub fn get_extinction(&self, species: usize) -> Option<u16> {
+        self.extinct[species]
+    }
+
+    ///the genes at the start of the first epoch
+    pub fn get_start_stats(&self, species: usize) -> SpeciesStats {
+        self.history.first().map(|h| h[species].clone()).unwrap_or_default()
+    }
+
+    ///the genes at the start of the last epoch
+    pub fn get_end_stats(&self, species: usize) -> SpeciesStats {
+        self.history.last().map(|h| h[species].clone()).unwrap_or_default()
+    }
+}
+
+/// a species of the food web with the indices of the species it interacts with
+struct Population<G: Genome> {
+    config: SpeciesConfig,
+    etype: EatingType,
+    eats_plants: bool,
+    ///species this one eats
+    prey: Vec<usize>,
+    ///species this one watches out for
+    predators: Vec<usize>,
+    members: SpatialMap<Creature<G>>,
+    ///the genes its genomes carry, built from the config
+    schema: Schema,
+    ///what the genomes share from one generation to the next
+    shared: G::Shared,
+}
+
+impl<G: Genome> Population<G> {
+    fn new(config: &SimulationConfig, species: usize) -> Population<G> {
+        let s = config.species[species].clone();
+        let index_of = |name: &String| config.species_index(name).expect("unknown species");
+        let eats_plants = s.diet.iter().any(|d| d == PLANTS);
+        let prey: Vec<usize> = s.diet.iter().filter(|d| *d != PLANTS).map(index_of).collect();
+        let predators = s.predators.iter().map(index_of).collect();
+        let etype = match (eats_plants, prey.is_empty()) {
+            (true, false) => EatingType::Omnivore,
+            (false, false) => EatingType::Carnivore,
+            _ => EatingType::Herbivore,
+        };
+        let schema = G::schema(&config.genes, &s).expect("invalid genes");
+        Population { config: s, etype, eats_plants, prey, predators, members: SpatialMap::new(), schema, shared: G::Shared::default() }
+    }
+}
+
+pub struct BasicSimulation <G : Genome>{
+    epochs: u16,
+    sim_time: u16,
+    mutation_chance: i32,
+    config: SimulationConfig,
+    seed: u64,
+    ///the only source of randomness, so a seed always yields the same run
+    rng: ChaCha8Rng,
+    file: File,
+    ///structured output next to the text log
+    sink: Option<Box<dyn Sink>>,
+    ///every change of the world, for replays
+    events: Option<EventLog>,
+    ///png and gif images of every step
+    renderer: Option<Renderer>,
+    plants: SpatialMap<bool>,
+    populations: Vec<Population<G>>,
+    genealogy: Genealogy,
+    ///epochs after which the state is saved, with the path prefix of the files
+    checkpoints: Option<(Vec<u16>, PathBuf)>,
+    ///whether the header of the log is written
+    started: bool,
+    ///whether the other species are simulated further once one died out
+    keep_going: bool,
+    ///steps done in the current epoch
+    step: u16,
+    finished: bool,
+    pub res: SimulationResult,
+}
+
+/// everything needed to continue a simulation exactly where it was saved
+///the version is checked before the rest is read
+#[derive(Deserialize)]
+#[serde(bound = "")]
+struct Checkpoint<G: Genome> {
+    epochs: u16,
+    sim_time: u16,
+    mutation_chance: i32,
+    config: SimulationConfig,
+    seed: u64,
+    rng: ChaCha8Rng,
+    plants: SpatialMap<bool>,
+    ///the members of every species, in the order of the config
+    members: Vec<SpatialMap<Creature<G>>>,
+    shared: Vec<G::Shared>,
+    genealogy: Genealogy,
+    keep_going: bool,
+    res: SimulationResult,
+}
+
+/// the same fields as [`Checkpoint`], borrowed from the running simulation
+#[derive(Serialize)]
+#[serde(bound = "")]
+struct SavedState<'a, G: Genome> {
+    version: u32,
+    epochs: u16,
+    sim_time: u16,
+    mutation_chance: i32,
+    config: &'a SimulationConfig,
+    seed: u64,
+    rng: &'a ChaCha8Rng,
+    plants: &'a SpatialMap<bool>,
+    members: Vec<&'a SpatialMap<Creature<G>>>,
+    shared: Vec<&'a G::Shared>,
+    genealogy: &'a Genealogy,
+    keep_going: bool,
+    res: &'a SimulationResult,
+}
+
+impl<G : Genome> Simulation for BasicSimulation<G>{
+    fn new(epochs: u16, sim_time: u16, mutation_chance: i32, config: SimulationConfig, seed: u64, mut file: File) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut plants:SpatialMap<bool> = SpatialMap::new();
+        //placing plants for food
+        for _ in 0..300 {
+            plants.insert_vacant(config.world.random_pos(&mut rng), || true);
+        };
+        //placing every species
+        let mut populations: Vec<Population<G>> = (0..config.species.len()).map(|i| Population::new(&config, i)).collect();
+        let mut genealogy = Genealogy::new();
+        for (i, p) in populations.iter_mut().enumerate() {
+            for _ in 0..p.config.count{
+                let k = config.world.random_pos(&mut rng);
+                p.members.insert_vacant(k, || Creature { id: genealogy.birth(i, None, 1), genome: G::new(p.etype.clone(), &p.schema, &p.config, &mut rng) });
+            };
+        }
+
+        file_print(&mut file,"Simulation Start:\n".to_string());
+        let res = SimulationResult::new(config.species.len());
+        BasicSimulation {
+            epochs, sim_time, mutation_chance, config, seed, rng, file, sink: None, events: None, renderer: None,
+            plants, populations, genealogy, checkpoints: None, keep_going: false, started: false, step: 0, finished: false, res
+        }
+    }
+
+    fn run(&mut self){
+        while self.advance() {}
+    }
+}
+
+impl<G: Genome> BasicSimulation<G> {
+    ///runs the next simulation step, the start and end of an epoch are handled with it.
+    ///returns false once the run is over
+    pub fn advance(&mut self) -> bool {
+        if self.finished {
+            return false;
+        }
+        if !self.started {
+            self.write_header();
+            self.started = true;
+        }
+        if self.step == 0 {
+            if self.res.epoch >= self.epochs {
+                self.finish();
+                return false;
+            }
+            self.begin_epoch();
+        }
+        let e = self.res.epoch - 1;
+        if self.step < self.sim_time {
+            self.simulation_step(e, self.step);
+            self.step += 1;
+        }
+        if self.step >= self.sim_time {
+            self.step = 0;
+            if !self.end_epoch(e) {
+                self.finish();
+                return false;
+            }
+        }
+        true
+    }
+
+    fn write_header(&mut self) {
+        file_print(&mut self.file, format!("SEED: {}\n", self.seed));
+        file_print(&mut self.file, format!("EPOCHS: {}\nSIM_TIME: {}\nMUTATION_CHANCE: {}\n",
+            self.epochs, self.sim_time, self.mutation_chance
+        ));
+        file_print(&mut self.file, format!("WORLD: {}x{} {:?}\nPERCEPTION: {:?}\n", self.config.world.width, self.config.world.height, self.config.world.topology, self.config.perception));
+        file_print(&mut self.file, format!("PLANT_ENERGY: {}\nMEAT_EFFICIENCY: {}\nSTRENGTH_CONTEST: {}\nPLANT DECREASE: {}\nPLANTS_GET_HALFED_AT: {}\n",
+            self.config.plant_energy, self.config.meat_efficiency, self.config.strength_contest, self.config.slow_plant_decrease, self.config.half_plant_at
+        ));
+        for p in &self.populations {
+            file_print(&mut self.file, format!("SPECIES: {} NUM: {} EXTRA: {} DIET: [{}] PREDATORS: [{}]\n",
+                p.config.name, p.config.count, p.config.extra_mutation_chance, p.config.diet.join(", "), p.config.predators.join(", ")
+            ));
+        }
+        if self.res.epoch > 0 {
+            file_print(&mut self.file, format!("RESUMED AFTER EPOCH: {}\n", self.res.epoch));
+        }
+    }
+
+    fn begin_epoch(&mut self) {
+        let e = self.res.epoch;
+        self.res.epoch += 1;
+        if let Some(log) = &mut self.events {
+            log.frame(e+1, 0);
+        }
+        //Epoch Output
+        file_print(&mut self.file, "###########################\n".to_string());
+        file_print(&mut self.file, format!("------EPOCH: {}---------\n", e+1));
+        file_print(&mut self.file, "###########################\n".to_string());
+        let mut stats = vec![];
+        let columns = gene_names(&self.populations);
+        for i in 0..self.populations.len() {
+            let p = &self.populations[i];
+            let keys = p.members.sorted_keys();
+            for g in &keys{
+                let h = p.members.get(g).expect("genome not available");
+                file_print(&mut self.file,format!("{}\n",h.to_string(&p.schema)));
+                if let Some(sink) = &mut self.sink {
+                    sink.write(&Record::Genome { epoch: e+1, stage: "start", species: &p.config.name, id: h.id, pos: *g, genes: gene_columns(&columns, &p.schema, &h.genome) });
+                }
+            }
+            let species = SpeciesStats::of(keys.iter().map(|g| &p.members.get(g).expect("genome not available").genome), &p.schema);
+            if let Some(sink) = &mut self.sink {
+                for (gene, g) in species.genes() {
+                    sink.write(&Record::GeneStat { epoch: e+1, species: &self.populations[i].config.name, gene, stats: g });
+                }
+            }
+            stats.push(species);
+        }
+        self.res.history.push(stats);
+        self.capture(e+1, 0);
+    }
+
+    fn simulation_step(&mut self, e: u16, s: u16) {
+        if self.config.watching {
+            animate(&self.config.world, &self.plants, &self.populations);
+        }
+        for i in 0..self.populations.len() {
+            self.move_species(i);
+        }
+        let counts: Vec<String> = self.populations.iter().map(|p| format!("{}: {}", p.config.name, p.members.len())).collect();
+        file_print(&mut self.file,format!("epoch: {} simulation step: {} -> {}\n",e+1,s+1,counts.join(" ")));
+        if let Some(sink) = &mut self.sink {
+            for p in &self.populations {
+                sink.write(&Record::Step { epoch: e+1, step: s+1, species: &p.config.name, count: p.members.len() });
+            }
+        }
+        if let Some(log) = &mut self.events {
+            log.frame(e+1, s+1);
+        }
+        self.capture(e+1, s+1);
+    }
+
+    fn capture(&mut self, epoch: u16, step: u16) {
+        if let Some(renderer) = &mut self.renderer {
+            let creatures = self.populations.iter().enumerate()
+                .flat_map(|(i, p)| p.members.iter().map(move |(pos, c)| (i, pos, &c.genome)));
+            renderer.capture(epoch, step, &self.config.world, self.plants.keys(), creatures);
+        }
+    }
+
+    ///selection, new plants and the next generation, false if a species died out
+    fn end_epoch(&mut self, e: u16) -> bool {
+        if let Some(renderer) = &mut self.renderer {
+            renderer.end_epoch();
+        }
+        for (i, p) in self.populations.iter_mut().enumerate() {
+            //removing starved creatures <------SELECTION
+            for k in p.members.sorted_keys() {
+                if !p.members.get(&k).expect("no genome").has_enough_energy(&p.schema) {
+                    let dead = p.members.remove(&k).expect("no genome");
+                    self.genealogy.death(dead.id, e+1, DeathCause::Starved);
+                    if let Some(log) = &mut self.events {
+                        log.push(Event::Death(i, k, DeathCause::Starved));
+                    }
+                }
+            }
+            self.res.average[i] += p.members.len() as i32;
+        }
+
+        file_print(&mut self.file, "surviving genes:\n".to_string());
+        let columns = gene_names(&self.populations);
+        for p in &self.populations {
+            for g in p.members.sorted_keys(){
+                let h = p.members.get(&g).expect("genome not available");
+                file_print(&mut self.file,format!("{}\n",h.to_string(&p.schema)));
+                if let Some(sink) = &mut self.sink {
+                    sink.write(&Record::Genome { epoch: e+1, stage: "survivor", species: &p.config.name, id: h.id, pos: g, genes: gene_columns(&columns, &p.schema, &h.genome) });
+                }
+            }
+        }
+        for p in &self.populations {
+            file_print(&mut self.file,format!("remaining {}: {}\n", p.config.name, p.members.len()));
+        }
+        if let Some(sink) = &mut self.sink {
+            for (i, p) in self.populations.iter().enumerate() {
+                sink.write(&Record::Epoch {
+                    epoch: e+1,
+                    species: &p.config.name,
+                    survivors: p.members.len(),
+                    average: self.res.average[i] as f64 / self.res.epoch as f64,
+                    died_out: p.members.len() <= 1,
+                });
+            }
+        }
+        for (i, p) in self.populations.iter().enumerate() {
+            if p.members.len() <= 1 && self.res.extinct[i].is_none() {
+                file_print(&mut self.file,format!("{} died out\n", p.config.name));
+                self.res.extinct[i] = Some(e+1);
+                self.res.die_out.get_or_insert_with(|| p.config.name.clone());
+            }
+        }
+        if self.res.die_out.is_some() && !self.keep_going || self.res.extinct.iter().all(|x| x.is_some()) {
+            return false;
+        }
+        //the survivors of the last epoch stay alive, there is no generation after it
+        if self.res.epoch >= self.epochs {
+            return false;
+        }
+        //removing plants
+        if let Some(log) = &mut self.events {
+            self.plants.sorted_keys().into_iter().for_each(|k| log.push(Event::Wither(k)));
+        }
+        self.plants.clear();
+        let mut  plants_to_place = 300 - (self.config.slow_plant_decrease * e as i32);
+        if e >= self.config.half_plant_at {
+            plants_to_place /= 2
+        }
+        //replacing plants
+        for _ in 0..plants_to_place {
+            let k = self.config.world.random_pos(&mut self.rng);
+            if self.plants.insert_vacant(k, || true).is_some() {
+                if let Some(log) = &mut self.events {
+                    log.push(Event::Plant(k));
+                }
+            }
+        };
+
+        //placing every species <----- CROSSOVER AND MUTATION
+        for (i, p) in self.populations.iter_mut().enumerate() {
+            let keys = p.members.sorted_keys();
+            //a species that died out gets no next generation, its last member is removed
+            let next = if self.res.extinct[i].is_some() { SpatialMap::new() } else { place_genom(&keys, &p.members, (&p.config, &p.schema, self.mutation_chance, &mut p.shared), &self.config.world, &mut self.rng, (&mut self.genealogy, i, e+2)) };
+            if let Some(text) = G::describe_shared(&p.shared).filter(|_| self.res.extinct[i].is_none()) {
+                file_print(&mut self.file, format!("{} {}\n", p.config.name, text));
+            }
+            //the parents make room for their children
+            for k in keys {
+                self.genealogy.death(p.members.get(&k).expect("no genome").id, e+1, DeathCause::Culled);
+                if let Some(log) = &mut self.events {
+                    log.push(Event::Death(i, k, DeathCause::Culled));
+                }
+            }
+            p.members = next;
+            if let Some(log) = &mut self.events {
+                p.members.sorted_keys().into_iter().for_each(|k| log.push(Event::Spawn(i, k)));
+            }
+        }
+        if let Some((at, prefix)) = &self.checkpoints {
+            if at.contains(&(e+1)) {
+                self.save(Path::new(&format!("{}.epoch{}.checkpoint.json", prefix.display(), e+1)));
+            }
+        }
+        true
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+        if let Some(log) = &mut self.events {
+            //the selection of the last epoch, shown as the start of the next one
+            if log.has_pending() {
+                log.frame(self.res.epoch + 1, 0);
+            }
+            log.finish();
+        }
+        let founders = self.genealogy.surviving_founders();
+        let surviving: Vec<String> = self.populations.iter().enumerate().map(|(i, p)| {
+            let n = founders.iter().filter(|(id, _)| self.genealogy.get(*id).species == i).count();
+            format!("{}: {}", p.config.name, n)
+        }).collect();
+        file_print(&mut self.file, format!("founders with living descendants: {}\n", surviving.join(" ")));
+        if let Some(sink) = &mut self.sink {
+            for (id, l) in self.genealogy.iter() {
+                sink.write(&Record::Lineage {
+                    id,
+                    species: &self.populations[l.species].config.name,
+                    parents: l.parents,
+                    born: l.born,
+                    died: l.death.map(|d| d.0),
+                    cause: l.death.map(|d| d.1.name()),
+                });
+            }
+            sink.finish();
+        }
+    }
+}
+
+impl<G: Genome> BasicSimulation<G> {
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    ///saves the state after each of the epochs `at` to `<prefix>.epoch<n>.checkpoint.json`
+    pub fn set_checkpoints(&mut self, at: Vec<u16>, prefix: PathBuf) {
+        self.checkpoints = Some((at, prefix));
+    }
+
+    ///keeps simulating the other species once one died out, the run ends when all are gone
+    pub fn set_continue_after_die_out(&mut self, keep_going: bool) {
+        self.keep_going = keep_going;
+    }
+
+    ///writes the complete state, so [`BasicSimulation::resume`] continues with the next epoch
+    pub fn save(&self, path: &Path) {
+        let checkpoint = SavedState {
+            version: CHECKPOINT_VERSION,
+            epochs: self.epochs,
+            sim_time: self.sim_time,
+            mutation_chance: self.mutation_chance,
+            config: &self.config,
+            seed: self.seed,
+            rng: &self.rng,
+            plants: &self.plants,
+            members: self.populations.iter().map(|p| &p.members).collect(),
+            shared: self.populations.iter().map(|p| &p.shared).collect(),
+            genealogy: &self.genealogy,
+            keep_going: self.keep_going,
+            res: &self.res,
+        };
+        let text = serde_json::to_string(&checkpoint).expect("could not serialize the simulation");
+        fs::write(path, text).expect("could not write checkpoint");
+    }
+
+    ///continues a saved simulation, optionally running to a different last epoch.
+    ///with the same epochs the rest of the run is identical to the one without a break
+    pub fn resume(path: &Path, epochs: Option<u16>, file: File) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read checkpoint {}: {}", path.display(), e))?;
+        let tree: serde_json::Value = serde_json::from_str(&text)
+            .map_err(|e| format!("invalid checkpoint {}: {}", path.display(), e))?;
+        if tree.get("version").and_then(|v| v.as_u64()) != Some(CHECKPOINT_VERSION as u64) {
+            return Err(format!("checkpoint {} is not of version {}", path.display(), CHECKPOINT_VERSION));
+        }
+        let c: Checkpoint<G> = serde_json::from_value(tree)
+            .map_err(|e| format!("invalid checkpoint {}: {}", path.display(), e))?;
+        c.config.validate()?;
+        if c.members.len() != c.config.species.len() || c.shared.len() != c.config.species.len() {
+            return Err(format!("checkpoint {} does not match its species", path.display()));
+        }
+        let mut populations: Vec<Population<G>> = (0..c.config.species.len()).map(|i| Population::new(&c.config, i)).collect();
+        for ((p, members), shared) in populations.iter_mut().zip(c.members).zip(c.shared) {
+            p.members = members;
+            p.shared = shared;
+        }
+        Ok(BasicSimulation {
+            epochs: epochs.unwrap_or(c.epochs), sim_time: c.sim_time, mutation_chance: c.mutation_chance,
+            config: c.config, seed: c.seed, rng: c.rng, file, sink: None, events: None, renderer: None,
+            plants: c.plants, populations, genealogy: c.genealogy, checkpoints: None, keep_going: c.keep_going,
+            started: false, step: 0, finished: false, res: c.res,
+        })
+    }
+
+    ///records every change of the world to `log`, starting with everything there is now
+    pub fn set_events(&mut self, mut log: EventLog) {
+        self.plants.sorted_keys().into_iter().for_each(|k| log.push(Event::Plant(k)));
+        for (i, p) in self.populations.iter().enumerate() {
+            p.members.sorted_keys().into_iter().for_each(|k| log.push(Event::Spawn(i, k)));
+        }
+        self.events = Some(log);
+    }
+
+    ///epoch and steps done in it, the epoch is 0 before the first one
+    pub fn progress(&self) -> (u16, u16) {
+        (self.res.epoch, self.step)
+    }
+
+    pub fn get_epochs(&self) -> u16 {
+        self.epochs
+    }
+
+    pub fn get_sim_time(&self) -> u16 {
+        self.sim_time
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    ///the number of members of every species
+    pub fn counts(&self) -> Vec<usize> {
+        self.populations.iter().map(|p| p.members.len()).collect()
+    }
+
+    pub fn plant_positions(&self) -> impl Iterator<Item = &(i32,i32)> {
+        self.plants.keys()
+    }
+
+    pub fn species_positions(&self, i: usize) -> impl Iterator<Item = &(i32,i32)> {
+        self.populations[i].members.keys()
+    }
+
+    pub fn plant_at(&self, pos: &(i32,i32)) -> bool {
+        self.plants.contains_key(pos)
+    }
+
+    ///every creature on `pos` with the index of its species
+    pub fn creatures_at(&self, pos: &(i32,i32)) -> Vec<(usize, &Creature<G>)> {
+        self.populations.iter().enumerate().filter_map(|(i, p)| p.members.get(pos).map(|c| (i, c))).collect()
+    }
+
+    ///draws every step with `renderer`
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.renderer = Some(renderer);
+    }
+
+    ///the gene names of the genome of every species, in the order of `Genome::genes`
+    pub fn species_gene_names(&self) -> Vec<Vec<&str>> {
+        self.populations.iter().map(|p| p.schema.names()).collect()
+    }
+
+    pub fn get_config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
+    ///the names of the species in the order of the config
+    pub fn species_names(&self) -> Vec<String> {
+        self.populations.iter().map(|p| p.config.name.clone()).collect()
+    }
+
+    pub fn get_world(&self) -> &World {
+        &self.config.world
+    }
+
+    ///also writes typed records of every step, epoch and genome to `sink`
+    pub fn set_sink(&mut self, sink: Box<dyn Sink>) {
+        self.sink = Some(sink);
+    }
+
+    ///every member of species `i` moves as often as its speed allows
+    fn move_species(&mut self, i: usize) {
+        let mut keys = self.populations[i].members.sorted_keys();
+        keys.shuffle(&mut self.rng);
+        for k in keys {
+            let mut pos = k;
+            let speed = match self.populations[i].members.get(&pos) {
+                Some(g) => g.get_speed().round() as i32,
+                None => continue, //eaten in the meantime
+            };
+            for _ in 0..speed {
+                if let Some(hunter) = self.hunter_at(i, &pos) {
+                    self.eat(hunter, i, &pos); // <--------SELECTION
+                    break;
+                }
+                //choosing direction
+                let direction = detect(pos, i, &self.populations, &self.plants, &self.config, &mut self.rng);
+                let temp = self.populations[i].members.remove(&pos).expect("genome does not exist");
+                //moving a step
+                let new_pos = self.config.world.step(pos, direction);
+                if new_pos != pos {
+                    self.record(Event::Move(i, pos, new_pos));
+                }
+                pos = new_pos;
+                self.populations[i].members.insert(new_pos, temp);
+
+                if self.populations[i].eats_plants && self.plants.remove(&new_pos).is_some() {
+                    self.populations[i].members.get_mut(&new_pos).expect("genome does not exist").increase_energy(self.config.plant_energy);
+                    self.record(Event::Graze(i, new_pos));
+                }
+                let hunter = &self.populations[i].members.get(&new_pos).expect("genome does not exist").genome;
+                let prey = self.populations[i].prey.iter().cloned().find(|&j| {
+                    self.populations[j].members.get(&new_pos)
+                        .is_some_and(|g| 0.0 < compare_strength(hunter, &g.genome, self.config.strength_contest))
+                });
+                if let Some(j) = prey {
+                    self.eat(i, j, &new_pos); // <--------SELECTION
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, event: Event) {
+        if let Some(log) = &mut self.events {
+            log.push(event);
+        }
+    }
+
+    ///a species standing on `pos` which hunts species `i` and wins the strength contest
+    fn hunter_at(&self, i: usize, pos: &(i32,i32)) -> Option<usize> {
+        let prey = self.populations[i].members.get(pos)?;
+        (0..self.populations.len()).find(|&j| {
+            self.populations[j].prey.contains(&i)
+                && self.populations[j].members.get(pos)
+                    .is_some_and(|g| 0.0 < compare_strength(&g.genome, &prey.genome, self.config.strength_contest))
+        })
+    }
+
+    ///the hunter at `pos` eats the prey standing on the same cell
+    fn eat(&mut self, hunter: usize, prey: usize, pos: &(i32,i32)) {
+        let dead = self.populations[prey].members.remove(pos).expect("prey not existend");
+        self.genealogy.death(dead.id, self.res.epoch, DeathCause::Eaten);
+        self.record(Event::Eat(hunter, prey, *pos));
+        self.populations[hunter].members.get_mut(pos)
+            .expect("hunter not existend")
+            .increase_energy(calculate_meat_efficiency(dead.get_weight(), self.config.meat_efficiency));
+    }
+}
+
+enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    fn dir(&self) -> (i32,i32){
+        match self {
+            Direction::Left => (-1,0),
+            Direction::Right => (1,0),
+            Direction::Up => (0,-1),
+            Direction::Down => (0,1),
+        }
+    }
+    fn ord(&self) -> usize {
+        match self {
+            Direction::Left => 0,
+            Direction::Right => 1,
+            Direction::Up => 2,
+            Direction::Down => 3,
+        }
+    }
+    fn get(num: usize) -> Direction{
+        match num {
+            0 => Direction::Left,
+            1 => Direction::Right,
+            2 => Direction::Up,
+            3 => Direction::Down,
+            _ => panic!("num to high")
+        }
+    }
+}
+
+fn calculate_reward(dir:&(i32,i32) ,a: &(i32,i32), b:&(i32,i32), reward: i32) -> i32 {
+    let c = (a.0 + dir.0, a.1 + dir.1);
+    let x = if c.0 > b.0 {
+        c.0 - b.0
+    }else{
+        b.0 - c.0
+    };
+    let y = if c.1 > b.1 {
+        c.1 - b.1
+    }else{
+        b.1 - c.1
+    };
+    if x+y == 0 {
+        return reward;
+    }
+    reward/(x+y)
+}
+fn calculate_distance_reward<T>(current: &T, g: &(i32,i32) , xy: &(i32,i32),directions: &mut [i32;4], num:u8)where T: Genome{
+    //Left
+    directions[Direction::Left.ord()] += calculate_reward(
+        &Direction::Left.dir(), g, xy, current.get_eval(num));
+    //Right
+    directions[Direction::Right.ord()] += calculate_reward(
+        &Direction::Right.dir(), g, xy, current.get_eval(num));
+    //Up
+    directions[Direction::Up.ord()] += calculate_reward(
+        &Direction::Up.dir(), g, xy, current.get_eval(num));
+    //Down
+    directions[Direction::Down.ord()] += calculate_reward(
+        &Direction::Down.dir(), g, xy, current.get_eval(num));
+}
+
+///chooses the next step of the member of species `i` at `h`.
+///eval 1 weights predators, eval 2 prey, eval 3 plants and `kin_eval` the own species
+fn detect <G,R> (h: (i32,i32), i: usize, populations: &[Population<G>], plants: &SpatialMap<bool>, config: &SimulationConfig, rng: &mut R) -> (i32,i32)
+    where G: Genome, R: Rng
+{
+    let world = &config.world;
+    let own = &populations[i];
+    let current = &own.members.get(&h).expect("current genome not available").genome;
+    let mut detection = current.get_detection_range();
+    if let Some(max) = config.max_detection_range {
+        detection = detection.min(max);
+    }
+    let dr = detection.round() as i32;
+    let mut directions = [0,0,0,0];
+    let (parts, window) = perception(world, config.perception, h, dr);
+    //the cells of all given species inside the window, every cell once
+    let seen = |species: &[usize]| {
+        let mut cells: Vec<((i32,i32),(i32,i32))> = species.iter().flat_map(|j| visible(&populations[*j].members, &parts, window)).collect();
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    };
+    let predators = seen(&own.predators);
+    let prey = seen(&own.prey);
+    let plants = if own.eats_plants { visible(plants, &parts, window) } else { vec![] };
+    let senses = Senses {
+        plant: nearest(&h, plants.iter()),
+        prey: nearest(&h, prey.iter()),
+        predator: nearest(&h, predators.iter()),
+        range: dr as f32,
+        energy: current.get_energy(),
+    };
+    if let Some(scores) = current.choose_direction(&senses) {
+        directions = scores.map(|s| (s * BRAIN_SCALE).round() as i32);
+    } else {
+        for (_, xy) in &predators {
+            calculate_distance_reward(current, &h, xy, &mut directions, 1);
+        }
+        for (_, xy) in &prey {
+            calculate_distance_reward(current, &h, xy, &mut directions, 2);
+        }
+        if own.config.kin_eval > 0 {
+            for (cell, xy) in seen(&[i]){
+                if cell != h {
+                    calculate_distance_reward(current, &h, &xy, &mut directions, own.config.kin_eval);
+                }
+            }
+        }
+        for (_, xy) in &plants {
+            calculate_distance_reward(current, &h, xy, &mut directions, 3);
+        }
+    }
+    let r = rng.gen_range(0..4);
+    //prevent loss of gene by collision, a wall counts as collision with itself
+    for (i, d) in directions.iter_mut().enumerate(){
+        if own.members.contains_key(&world.step(h, Direction::get(i).dir())){
+            *d = i32::MIN;
+        }
+    };
+    let mut choice = (directions[r], Direction::get(r));
+    for (i, d) in directions.iter().enumerate(){
+        if *d > choice.0{
+            choice = (*d, Direction::get(i));
+        }
+    };
+
+    if choice.0 < 0 {
+        return (0, 0);
+    }
+    (choice.1.dir().0, choice.1.dir().1)
+
+}
+
+///the parts of the world a creature at `h` looks into and whether a position of the window
+///lies within its detection range `dr`
+fn perception(world: &World, metric: Metric, h: (i32,i32), dr: i32) -> (Vec<Part>, impl Fn(&(i32,i32)) -> bool + Copy) {
+    //a torus window must not reach around the whole world and see cells twice
+    let (rx, ry) = match world.topology {
+        Topology::Torus => (dr.min((world.width - 1) / 2), dr.min((world.height - 1) / 2)),
+        _ => (dr, dr),
+    };
+    let parts = world.visible_parts(
+        (h.0.saturating_sub(rx), h.1.saturating_sub(ry)),
+        (h.0.saturating_add(rx), h.1.saturating_add(ry)),
+    );
+    (parts, move |xy: &(i32,i32)| metric.contains(xy.0 as i64 - h.0 as i64, xy.1 as i64 - h.1 as i64, dr as i64))
+}
+
+///the offset to the closest of the `seen` cells in window coordinates, of equally close ones the
+///smallest offset, so the order the cells were found in does not matter
+fn nearest<'a>(h: &(i32,i32), seen: impl Iterator<Item = &'a ((i32,i32),(i32,i32))>) -> Option<(i32,i32)> {
+    seen.map(|(_, xy)| (xy.0 - h.0, xy.1 - h.1))
+        .min_by_key(|d| (d.0.abs() + d.1.abs(), *d))
+}
+
+///the used cells of `map` inside the window parts, each with its position in the coordinates
+///of the window (which may lie across the border of a torus, the distances are calculated with it)
+fn visible<T>(map: &SpatialMap<T>, parts: &[Part], window: impl Fn(&(i32,i32)) -> bool) -> Vec<((i32,i32),(i32,i32))> {
+    let mut cells = vec![];
+    for (min, max, shift) in parts {
+        for cell in map.within(*min, *max) {
+            let xy = (cell.0 + shift.0, cell.1 + shift.1);
+            if !window(&xy){//the shape of the perception
+                continue;
+            }
+            cells.push((cell, xy));
+        }
+    }
+    cells
+}
+
+///draws the first letter of every species, later species on top
+fn animate<G: Genome>(world: &World, plants: &SpatialMap<bool>, populations: &[Population<G>]){
+    let used = plants.keys().chain(populations.iter().flat_map(|p| p.members.keys())).cloned();
+    draw(world, used, |pos| plants.contains_key(pos), |pos| {
+        populations.iter().rev().find(|p| p.members.contains_key(pos)).map(|p| letter(&p.config.name))
+    });
+    thread::sleep(time::Duration::from_millis(MILLIS_PER_FRAME))
+}
+
+///the letter a species is drawn with
+pub fn letter(name: &str) -> char {
+    name.chars().next().unwrap_or('?').to_ascii_uppercase()
+}
+
+///prints the world with the creature `species` finds on a cell, else '*' for plants.
+///an unbounded world is drawn as far as the `used` cells reach
+pub fn draw(world: &World, used: impl Iterator<Item = (i32,i32)>, plant: impl Fn(&(i32,i32)) -> bool, species: impl Fn(&(i32,i32)) -> Option<char>){
+    let (mut min, mut max) = (world.min(), world.max());
+    if world.topology == Topology::Unbounded {
+        for pos in used {
+            min = (min.0.min(pos.0), min.1.min(pos.1));
+            max = (max.0.max(pos.0), max.1.max(pos.1));
+        }
+    }
+    match stdout().flush(){
+        Ok(_) => {
+            for y in min.1..=max.1 {
+                for x in min.0..=max.0{
+                    match species(&(x,y)) {
+                        Some(c) => print!("{}", c),
+                        None if plant(&(x,y)) => print!("*"),
+                        None => print!("_"),
+                    }
+                }
+                println!();
+
+            }
+        }
+        Err(e) => panic!("in animate: {}",e)
+    };
+}
+
+///the next generation of a species, bred with the config, the schema, the mutation chance and the shared
+///state of the genomes of `breeding`. `family` registers every child of `species` born in epoch `born`
+fn place_genom<T, R>(keys: &[(i32,i32)], map: &SpatialMap<Creature<T>>, breeding: (&SpeciesConfig, &Schema, i32, &mut T::Shared), world: &World, rng: &mut R, family: (&mut Genealogy, usize, u16)) -> SpatialMap<Creature<T>> where T: Genome, R: Rng{
+    let (config, schema, mutation_chance, shared) = breeding;
+    let (genealogy, species, born) = family;
+    let chance = mutation_chance + config.extra_mutation_chance;
+    let parents: Vec<&Creature<T>> = keys.iter().map(|k| map.get(k).expect("no parent")).collect();
+    let genomes: Vec<&T> = parents.iter().map(|p| &p.genome).collect();
+    T::prepare_breeding(&genomes, config, shared);
+    let mut next_gen: SpatialMap<Creature<T>> = SpatialMap::new();
+    for _ in 0..config.count{
+        let k = world.random_pos(rng);
+        let (first, second) = T::choose_parents(&genomes, shared, rng);
+        let (parent1, parent2) = (parents[first], parents[second]);
+        let child = next_gen.insert_vacant(k, || Creature {
+            id: genealogy.birth(species, Some((parent1.id, parent2.id)), born),
+            genome: parent1.crossover(parent2, schema, config.crossover, rng),// <--- CROSSOVER
+        });
+        if let Some(child) = child {
+            child.mutate(chance, schema, config, shared, rng);//<-----MUTATE
+        }
+    };
+    next_gen
+}
+///the gene names of the genomes of all species, each once, with "mutation_rate" last
+fn gene_names<G: Genome>(populations: &[Population<G>]) -> Vec<&str> {
+    let mut names: Vec<&str> = vec![];
+    for g in populations.iter().flat_map(|p| &p.schema.genes) {
+        if !names.contains(&g.name.as_str()) {
+            names.push(&g.name);
+        }
+    }
+    names.push("mutation_rate");
+    names
+}
+
+///the value of every gene of `columns` the genome carries, the order of its `schema`
+fn gene_columns<'a, G: Genome>(columns: &[&'a str], schema: &Schema, genome: &G) -> Vec<(&'a str, Option<f32>)> {
+    let (names, values) = (schema.names(), genome.genes());
+    columns.iter().map(|c| (*c, names.iter().position(|n| n == c).map(|i| values[i]))).collect()
+}
+
+pub fn file_print(file: &mut File, string:String){
+    file.write_all(string.as_bytes()).expect("write went wrong");
+}
+
+fn compare_strength<T: Genome,E: Genome>(carni: &E, herbi: &T, strength_contest: bool) -> f32 {
+    if strength_contest {
+        carni.get_power() - herbi.get_power()
+    }else{
+        1.0
+    }
+}
+
+fn calculate_meat_efficiency(weight: f32, meat_efficiency: f32) -> f32{
+    weight * meat_efficiency
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METRICS: [Metric; 3] = [Metric::Manhattan, Metric::Euclidean, Metric::Chebyshev];
+
+    ///a world with something on every cell
+    fn full(topology: Topology) -> (World, SpatialMap<bool>) {
+        let world = World { width: 31, height: 31, topology };
+        let mut map = SpatialMap::new();
+        let (min, max) = (world.min(), world.max());
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                map.insert((x, y), true);
+            }
+        }
+        (world, map)
+    }
+
+    ///the offsets of the cells a creature at `h` sees, sorted
+    fn seen(world: &World, map: &SpatialMap<bool>, metric: Metric, h: (i32, i32), dr: i32) -> Vec<(i32, i32)> {
+        let (parts, window) = perception(world, metric, h, dr);
+        let mut offsets: Vec<(i32, i32)> = visible(map, &parts, window).into_iter().map(|(_, xy)| (xy.0 - h.0, xy.1 - h.1)).collect();
+        offsets.sort_unstable();
+        offsets
+    }
+
+    #[test]
+    fn perception_is_symmetric() {
+        for topology in [Topology::Walls, Topology::Torus, Topology::Unbounded] {
+            let (world, map) = full(topology);
+            for metric in METRICS {
+                for dr in 0..=6 {
+                    let offsets = seen(&world, &map, metric, (0, 0), dr);
+                    for (dx, dy) in &offsets {
+                        assert!(offsets.contains(&(-dx, *dy)) && offsets.contains(&(*dx, -dy)) && offsets.contains(&(*dy, *dx)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn perception_does_not_depend_on_the_position() {
+        for (topology, positions) in [
+            (Topology::Walls, vec![(0, 0), (-8, 5), (7, 8), (-9, -9)]),
+            (Topology::Torus, vec![(0, 0), (-15, -15), (15, 15), (-15, 3), (4, 14)]),
+            (Topology::Unbounded, vec![(0, 0), (-9, 9), (400, -300)]),
+        ] {
+            let (world, mut map) = full(topology);
+            if topology == Topology::Unbounded {
+                //the cells around the far position, the same as the ones around the others
+                for x in 390..=410 {
+                    for y in -310..=-290 {
+                        map.insert((x, y), true);
+                    }
+                }
+            }
+            for metric in METRICS {
+                for dr in [0, 1, 3, 6] {
+                    let middle = seen(&world, &map, metric, positions[0], dr);
+                    for h in &positions[1..] {
+                        assert_eq!(seen(&world, &map, metric, *h, dr), middle, "{:?} {:?} at {:?} range {}", topology, metric, h, dr);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn torus_window_across_the_border_sees_what_one_in_the_middle_sees() {
+        let (world, map) = full(Topology::Torus);
+        for metric in METRICS {
+            for dr in [2, 5, 9] {
+                let middle = seen(&world, &map, metric, (0, 0), dr);
+                let (parts, window) = perception(&world, metric, world.min(), dr);
+                let mut cells: Vec<(i32, i32)> = visible(&map, &parts, window).into_iter().map(|(cell, _)| cell).collect();
+                //the corner sees across two borders, every cell it sees lies in the world
+                assert!(cells.iter().all(|c| world.contains(c)));
+                assert_eq!(seen(&world, &map, metric, world.min(), dr), middle);
+                //and every cell once
+                let count = cells.len();
+                cells.sort_unstable();
+                cells.dedup();
+                assert_eq!(cells.len(), count);
+            }
+        }
+    }
+
+    #[test]
+    fn torus_window_larger_than_the_world_sees_every_cell_once() {
+        let (world, map) = full(Topology::Torus);
+        let (parts, window) = perception(&world, Metric::Chebyshev, (3, -7), 100);
+        let mut cells: Vec<(i32, i32)> = visible(&map, &parts, window).into_iter().map(|(cell, _)| cell).collect();
+        cells.sort_unstable();
+        cells.dedup();
+        assert_eq!(cells.len(), map.len());
+    }
+
+    #[test]
+    fn survivors_of_the_last_epoch_stay_alive() {
+        let file = File::create(std::env::temp_dir().join("ki_hausarbeit_last_epoch.txt")).expect("file problem");
+        let mut sim: BasicSimulation<crate::genome::BasicGenome> = BasicSimulation::new(3, 10, 150, SimulationConfig::default(), 7, file);
+        sim.set_continue_after_die_out(true);
+        sim.run();
+        let mut members: Vec<u64> = sim.populations.iter().flat_map(|p| p.members.iter().map(|(_, c)| c.id)).collect();
+        let mut alive: Vec<u64> = sim.genealogy.iter().filter(|(_, l)| l.death.is_none()).map(|(id, _)| id).collect();
+        members.sort_unstable();
+        alive.sort_unstable();
+        assert!(!members.is_empty());
+        assert_eq!(members, alive);
+        assert!(sim.genealogy.iter().all(|(_, l)| l.born <= 3));
+    }
+}
@@ -0,0 +1,230 @@
+//!
+//! everything related to finding things by their position
+//!
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// side length of the square buckets the positions are sorted into
+const BUCKET_SIZE: i32 = 8;
+
+/// a map from positions to values which can answer "what is inside this rectangle"
+/// without looking at every cell of it
+#[derive(Debug, Clone)]
+pub struct SpatialMap<T> {
+    cells: HashMap<(i32, i32), T>,
+    buckets: HashMap<(i32, i32), Vec<(i32, i32)>>,
+}
+
+impl<T> Default for SpatialMap<T> {
+    fn default() -> Self {
+        SpatialMap { cells: HashMap::new(), buckets: HashMap::new() }
+    }
+}
+
+impl<T> SpatialMap<T> {
+    pub fn new() -> SpatialMap<T> {
+        SpatialMap::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn contains_key(&self, pos: &(i32, i32)) -> bool {
+        self.cells.contains_key(pos)
+    }
+
+    pub fn get(&self, pos: &(i32, i32)) -> Option<&T> {
+        self.cells.get(pos)
+    }
+
+    pub fn get_mut(&mut self, pos: &(i32, i32)) -> Option<&mut T> {
+        self.cells.get_mut(pos)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &(i32, i32)> {
+        self.cells.keys()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&(i32, i32), &T)> {
+        self.cells.iter()
+    }
+
+    ///keys in a fixed order, so the HashMap iteration order never influences a run
+    pub fn sorted_keys(&self) -> Vec<(i32, i32)> {
+        let mut keys: Vec<(i32, i32)> = self.cells.keys().cloned().collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    pub fn insert(&mut self, pos: (i32, i32), value: T) -> Option<T> {
+        let old = self.cells.insert(pos, value);
+        if old.is_none() {
+            self.buckets.entry(bucket(&pos)).or_default().push(pos);
+        }
+        old
+    }
+
+    ///inserts the value made by `f` only if `pos` is still free
+    pub fn insert_vacant(&mut self, pos: (i32, i32), f: impl FnOnce() -> T) -> Option<&mut T> {
+        if self.cells.contains_key(&pos) {
+            return None;
+        }
+        self.insert(pos, f());
+        self.cells.get_mut(&pos)
+    }
+
+    pub fn remove(&mut self, pos: &(i32, i32)) -> Option<T> {
+        let old = self.cells.remove(pos)?;
+        let b = bucket(pos);
+        if let Some(list) = self.buckets.get_mut(&b) {
+            if let Some(i) = list.iter().position(|p| p == pos) {
+                list.swap_remove(i);
+            }
+            if list.is_empty() {
+                self.buckets.remove(&b);
+            }
+        }
+        Some(old)
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.buckets.clear();
+    }
+
+    ///every used position inside the rectangle `min`..=`max`, in no particular order
+    pub fn within(&self, min: (i32, i32), max: (i32, i32)) -> Vec<(i32, i32)> {
+        let inside = |p: &&(i32, i32)| min.0 <= p.0 && p.0 <= max.0 && min.1 <= p.1 && p.1 <= max.1;
+        if min.0 > max.0 || min.1 > max.1 {
+            return vec![];
+        }
+        let (bmin, bmax) = (bucket(&min), bucket(&max));
+        let span = (bmax.0 as i64 - bmin.0 as i64 + 1) * (bmax.1 as i64 - bmin.1 as i64 + 1);
+        //a huge rectangle is cheaper to answer by looking at every used bucket
+        if span > self.buckets.len() as i64 {
+            return self.buckets.values().flatten().filter(inside).cloned().collect();
+        }
+        (bmin.0..=bmax.0)
+            .flat_map(|bx| (bmin.1..=bmax.1).map(move |by| (bx, by)))
+            .filter_map(|b| self.buckets.get(&b))
+            .flatten()
+            .filter(inside)
+            .cloned()
+            .collect()
+    }
+}
+
+/// written as a list of (position, value) pairs in sorted order, the buckets are rebuilt when reading
+impl<T: Serialize> Serialize for SpatialMap<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.sorted_keys().into_iter().map(|k| (k, &self.cells[&k])))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SpatialMap<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cells: Vec<((i32, i32), T)> = Vec::deserialize(deserializer)?;
+        let mut map = SpatialMap::new();
+        for (pos, value) in cells {
+            map.insert(pos, value);
+        }
+        Ok(map)
+    }
+}
+
+fn bucket(pos: &(i32, i32)) -> (i32, i32) {
+    (pos.0.div_euclid(BUCKET_SIZE), pos.1.div_euclid(BUCKET_SIZE))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    ///random positions around the origin, so buckets with negative coordinates are used too
+    fn scattered(n: usize, rng: &mut ChaCha8Rng) -> SpatialMap<usize> {
+        let mut map = SpatialMap::new();
+        for i in 0..n {
+            map.insert((rng.gen_range(-40..40), rng.gen_range(-40..40)), i);
+        }
+        map
+    }
+
+    fn brute_force(map: &SpatialMap<usize>, min: (i32, i32), max: (i32, i32)) -> Vec<(i32, i32)> {
+        map.sorted_keys().into_iter().filter(|p| min.0 <= p.0 && p.0 <= max.0 && min.1 <= p.1 && p.1 <= max.1).collect()
+    }
+
+    fn within(map: &SpatialMap<usize>, min: (i32, i32), max: (i32, i32)) -> Vec<(i32, i32)> {
+        let mut found = map.within(min, max);
+        found.sort_unstable();
+        found
+    }
+
+    ///every position is in exactly the bucket it belongs to and no bucket is empty
+    fn assert_consistent(map: &SpatialMap<usize>) {
+        let mut listed: Vec<(i32, i32)> = vec![];
+        for (b, list) in &map.buckets {
+            assert!(!list.is_empty(), "empty bucket {:?}", b);
+            for p in list {
+                assert_eq!(bucket(p), *b);
+                assert!(map.contains_key(p), "{:?} is only in its bucket", p);
+            }
+            listed.extend(list);
+        }
+        listed.sort_unstable();
+        assert_eq!(listed, map.sorted_keys());
+    }
+
+    #[test]
+    fn within_finds_what_a_brute_force_search_finds() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let map = scattered(600, &mut rng);
+        for _ in 0..500 {
+            let (x, y) = (rng.gen_range(-50..50), rng.gen_range(-50..50));
+            let r = rng.gen_range(0..3 * BUCKET_SIZE);
+            assert_eq!(within(&map, (x - r, y - r), (x + r, y + r)), brute_force(&map, (x - r, y - r), (x + r, y + r)));
+        }
+        //exactly on and next to the bucket borders, on both sides of zero
+        for (min, max) in [((-8, -8), (-1, -1)), ((-9, -9), (0, 0)), ((-1, -1), (0, 0)), ((0, 0), (7, 7)), ((7, -9), (8, 8)), ((-40, -40), (39, 39))] {
+            assert_eq!(within(&map, min, max), brute_force(&map, min, max));
+        }
+        assert!(map.within((3, 3), (2, 2)).is_empty());
+    }
+
+    #[test]
+    fn buckets_follow_remove_move_and_clear() {
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let mut map = scattered(300, &mut rng);
+        assert_consistent(&map);
+        for _ in 0..1000 {
+            let keys = map.sorted_keys();
+            let from = keys[rng.gen_range(0..keys.len())];
+            //a move, like a creature taking a step, often into a neighbouring bucket
+            let to = (from.0 + rng.gen_range(-1..=1), from.1 + rng.gen_range(-1..=1));
+            if !map.contains_key(&to) {
+                let value = map.remove(&from).expect("no value");
+                map.insert(to, value);
+            }
+            if rng.gen_bool(0.1) {
+                let gone = keys[rng.gen_range(0..keys.len())];
+                map.remove(&gone);
+                assert!(!map.contains_key(&gone));
+                assert!(map.within(gone, gone).is_empty());
+            }
+        }
+        assert_consistent(&map);
+        assert_eq!(within(&map, (-60, -60), (60, 60)), map.sorted_keys());
+        assert_eq!(map.remove(&(1000, 1000)), None);
+        map.clear();
+        assert_consistent(&map);
+        assert_eq!(map.len(), 0);
+        assert!(map.within((-60, -60), (60, 60)).is_empty());
+        map.insert((-3, 4), 1);
+        assert_consistent(&map);
+        assert_eq!(map.within((-8, 0), (-1, 7)), [(-3, 4)]);
+    }
+}
@@ -0,0 +1,319 @@
+//!
+//! everything related to statistics of genes and of batches
+//!
+use serde::{Deserialize, Serialize};
+
+use crate::genome::{Genome, GENES};
+use crate::schema::Schema;
+
+/// summary of one gene over all members of a species
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeneStats {
+    pub mean: f64,
+    ///population variance, the members are the whole species and not a sample of it
+    pub variance: f64,
+    pub min: f64,
+    pub max: f64,
+    ///mean of the two middle values for an even number of members
+    pub median: f64,
+}
+
+impl GeneStats {
+    /// all zero for no values
+    pub fn of(values: &[f64]) -> GeneStats {
+        if values.is_empty() {
+            return GeneStats::default();
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) { (sorted[middle - 1] + sorted[middle]) / 2.0 } else { sorted[middle] };
+        GeneStats { mean, variance, min: sorted[0], max: sorted[sorted.len() - 1], median }
+    }
+}
+
+/// the statistics of every gene of a species at one moment
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpeciesStats {
+    pub members: usize,
+    pub weight: GeneStats,
+    pub speed: GeneStats,
+    pub power: GeneStats,
+    pub detection: GeneStats,
+    pub eval_1: GeneStats,
+    pub eval_2: GeneStats,
+    pub eval_3: GeneStats,
+    ///the genes added in the config, by name
+    #[serde(default)]
+    pub other: Vec<(String, GeneStats)>,
+}
+
+impl SpeciesStats {
+    pub fn of<'a, G: Genome + 'a>(genomes: impl Iterator<Item = &'a G>, schema: &Schema) -> SpeciesStats {
+        let genomes: Vec<&G> = genomes.collect();
+        let gene = |f: &dyn Fn(&G) -> f64| GeneStats::of(&genomes.iter().map(|g| f(g)).collect::<Vec<f64>>());
+        SpeciesStats {
+            members: genomes.len(),
+            weight: gene(&|g| g.get_weight() as f64),
+            speed: gene(&|g| g.get_speed() as f64),
+            power: gene(&|g| g.get_power() as f64),
+            detection: gene(&|g| g.get_detection_range() as f64),
+            eval_1: gene(&|g| g.get_eval(1) as f64),
+            eval_2: gene(&|g| g.get_eval(2) as f64),
+            eval_3: gene(&|g| g.get_eval(3) as f64),
+            other: schema.genes.iter().enumerate().skip(GENES.len())
+                .map(|(i, s)| (s.name.clone(), gene(&|g| g.genes()[i] as f64)))
+                .collect(),
+        }
+    }
+
+    /// every gene by name, in the order of the schema
+    pub fn genes(&self) -> Vec<(&str, &GeneStats)> {
+        let mut genes = vec![
+            ("weight", &self.weight),
+            ("speed", &self.speed),
+            ("power", &self.power),
+            ("detection", &self.detection),
+            ("eval_1", &self.eval_1),
+            ("eval_2", &self.eval_2),
+            ("eval_3", &self.eval_3),
+        ];
+        genes.extend(self.other.iter().map(|(name, g)| (name.as_str(), g)));
+        genes
+    }
+}
+
+/// mean and sample standard deviation, zero for fewer than two values
+pub fn mean_sd(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    if values.len() < 2 {
+        return (mean, 0.0);
+    }
+    (mean, (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt())
+}
+
+/// the wilson score interval of `k` successes in `n` trials, `z` 1.96 gives 95%
+pub fn wilson(k: usize, n: usize, z: f64) -> (f64, f64) {
+    if n == 0 {
+        return (0.0, 1.0);
+    }
+    let (n, p) = (n as f64, k as f64 / n as f64);
+    let center = (p + z * z / (2.0 * n)) / (1.0 + z * z / n);
+    let half = z / (1.0 + z * z / n) * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt();
+    ((center - half).max(0.0), (center + half).min(1.0))
+}
+
+/// newcombe's interval of the difference `k1/n1 - k2/n2` built from the two wilson intervals
+pub fn newcombe(k1: usize, n1: usize, k2: usize, n2: usize, z: f64) -> (f64, f64) {
+    let (p1, p2) = (k1 as f64 / n1 as f64, k2 as f64 / n2 as f64);
+    let ((l1, u1), (l2, u2)) = (wilson(k1, n1, z), wilson(k2, n2, z));
+    let d = p1 - p2;
+    (d - ((p1 - l1).powi(2) + (u2 - p2).powi(2)).sqrt(), d + ((u1 - p1).powi(2) + (p2 - l2).powi(2)).sqrt())
+}
+
+/// pearson's chi-square test of independence of the `(successes, trials)` of every group.
+/// gives the statistic, the degrees of freedom and the p value, none if a column is empty
+pub fn chi_square(groups: &[(usize, usize)]) -> Option<(f64, usize, f64)> {
+    let k: usize = groups.iter().map(|g| g.0).sum();
+    let n: usize = groups.iter().map(|g| g.1).sum();
+    if groups.len() < 2 || k == 0 || k == n {
+        return None;
+    }
+    let rate = k as f64 / n as f64;
+    let x: f64 = groups.iter().map(|&(k, n)| {
+        let (expected_yes, expected_no) = (n as f64 * rate, n as f64 * (1.0 - rate));
+        (k as f64 - expected_yes).powi(2) / expected_yes + ((n - k) as f64 - expected_no).powi(2) / expected_no
+    }).sum();
+    let df = groups.len() - 1;
+    Some((x, df, gamma_q(df as f64 / 2.0, x / 2.0)))
+}
+
+/// cohen's d of two samples with the pooled standard deviation, none if it is zero
+pub fn cohen_d(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let ((ma, sa), (mb, sb)) = (mean_sd(a), mean_sd(b));
+    let (na, nb) = (a.len() as f64, b.len() as f64);
+    let pooled = (((na - 1.0) * sa * sa + (nb - 1.0) * sb * sb) / (na + nb - 2.0)).sqrt();
+    (pooled > 0.0).then(|| (mb - ma) / pooled)
+}
+
+/// the kaplan-meier estimate of surviving past every epoch from 1 to `last`.
+/// every run is `(epoch, died)`, runs without a death are censored at their epoch
+pub fn kaplan_meier(runs: &[(u16, bool)], last: u16) -> Vec<f64> {
+    let mut survival = 1.0;
+    (1..=last).map(|t| {
+        let at_risk = runs.iter().filter(|r| r.0 >= t).count();
+        let deaths = runs.iter().filter(|r| r.0 == t && r.1).count();
+        if at_risk > 0 {
+            survival *= 1.0 - deaths as f64 / at_risk as f64;
+        }
+        survival
+    }).collect()
+}
+
+/// the regularized upper incomplete gamma function Q(a, x), as in numerical recipes
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let front = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        //series of P(a, x)
+        let (mut term, mut sum, mut ap) = (1.0 / a, 1.0 / a, a);
+        while term.abs() > sum.abs() * 1e-15 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+        }
+        1.0 - sum * front
+    } else {
+        //continued fraction of Q(a, x) with lentz's method
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let (mut c, mut d) = (1.0 / tiny, 1.0 / b);
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            d = if d.abs() < tiny { 1.0 / tiny } else { 1.0 / d };
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            let step = d * c;
+            h *= step;
+            if (step - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        front * h
+    }
+}
+
+/// lanczos approximation of ln Γ(x) for x > 0
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [76.18009172947146, -86.50532032941677, 24.01409824083091, -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series: f64 = 1.000000000190015 + COEFFICIENTS.iter().enumerate().map(|(i, c)| c / (x + 1.0 + i as f64)).sum::<f64>();
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::config::SpeciesConfig;
+    use crate::genome::{BasicGenome, EatingType};
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() < tolerance
+    }
+
+    #[test]
+    fn gene_stats_of_odd_length() {
+        let s = GeneStats::of(&[3.0, 1.0, 4.0, 1.0, 5.0]);
+        assert!(close(s.mean, 2.8, 1e-12));
+        assert!(close(s.variance, 2.56, 1e-12));
+        assert_eq!((s.min, s.max, s.median), (1.0, 5.0, 3.0));
+    }
+
+    #[test]
+    fn gene_stats_of_even_length() {
+        let s = GeneStats::of(&[2.0, 8.0, 4.0, 6.0]);
+        assert!(close(s.mean, 5.0, 1e-12));
+        assert!(close(s.variance, 5.0, 1e-12));
+        assert_eq!((s.min, s.max, s.median), (2.0, 8.0, 5.0));
+    }
+
+    #[test]
+    fn gene_stats_of_nothing_is_zero() {
+        assert_eq!(GeneStats::of(&[]), GeneStats::default());
+        let s = GeneStats::of(&[7.0]);
+        assert_eq!((s.mean, s.variance, s.min, s.max, s.median), (7.0, 0.0, 7.0, 7.0, 7.0));
+    }
+
+    #[test]
+    fn species_stats_follow_the_genes() {
+        let schema = Schema::new(&[crate::schema::GeneConfig { name: "fur".to_owned(), ..Default::default() }]).expect("schema");
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let genomes: Vec<BasicGenome> = (0..9).map(|_| BasicGenome::new(EatingType::Herbivore, &schema, &SpeciesConfig::default(), &mut rng)).collect();
+        let stats = SpeciesStats::of(genomes.iter(), &schema);
+        assert_eq!(stats.members, 9);
+        let genes = stats.genes();
+        assert_eq!(genes.len(), schema.len());
+        for (i, (name, s)) in genes.into_iter().enumerate() {
+            assert_eq!(name, schema.genes[i].name);
+            let values: Vec<f64> = genomes.iter().map(|g| g.genes()[i] as f64).collect();
+            assert_eq!(*s, GeneStats::of(&values));
+        }
+    }
+
+    #[test]
+    fn mean_sd_uses_the_sample_sd() {
+        let (mean, sd) = mean_sd(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert!(close(mean, 5.0, 1e-12));
+        assert!(close(sd, (32.0f64 / 7.0).sqrt(), 1e-12));
+        assert_eq!(mean_sd(&[]), (0.0, 0.0));
+        assert_eq!(mean_sd(&[3.0]), (3.0, 0.0));
+    }
+
+    #[test]
+    fn wilson_matches_reference_values() {
+        let (low, high) = wilson(5, 10, 1.96);
+        assert!(close(low, 0.2366, 1e-4) && close(high, 0.7634, 1e-4));
+        let (low, high) = wilson(0, 10, 1.96);
+        assert!(close(low, 0.0, 1e-12) && close(high, 0.2775, 1e-4));
+        assert_eq!(wilson(0, 0, 1.96), (0.0, 1.0));
+    }
+
+    #[test]
+    fn newcombe_matches_reference_values() {
+        //example (a) of newcombe (1998), method 10
+        let (low, high) = newcombe(56, 70, 48, 80, 1.96);
+        assert!(close(low, 0.0524, 1e-4) && close(high, 0.3339, 1e-4));
+    }
+
+    #[test]
+    fn chi_square_matches_reference_values() {
+        let (x, df, p) = chi_square(&[(10, 20), (20, 30)]).expect("test");
+        assert!(close(x, 25.0 / 18.0, 1e-12));
+        assert_eq!(df, 1);
+        assert!(close(p, 0.238593, 1e-5));
+        assert!(chi_square(&[(0, 10), (0, 10)]).is_none());
+        assert!(chi_square(&[(10, 10), (10, 10)]).is_none());
+        assert!(chi_square(&[(3, 10)]).is_none());
+    }
+
+    #[test]
+    fn gamma_q_gives_critical_chi_square_values() {
+        //the 5% critical values for 1, 2 and 4 degrees of freedom
+        assert!(close(gamma_q(0.5, 3.841459 / 2.0), 0.05, 1e-6));
+        assert!(close(gamma_q(1.0, 5.991465 / 2.0), 0.05, 1e-6));
+        assert!(close(gamma_q(2.0, 9.487729 / 2.0), 0.05, 1e-6));
+        assert!(close(gamma_q(1.0, 0.5), (-0.5f64).exp(), 1e-12));
+        assert_eq!(gamma_q(3.0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn kaplan_meier_steps_at_deaths() {
+        let runs = [(2, true), (3, false), (4, true), (4, true), (5, false)];
+        let survival = kaplan_meier(&runs, 5);
+        let expected = [1.0, 0.8, 0.8, 0.8 / 3.0, 0.8 / 3.0];
+        assert_eq!(survival.len(), expected.len());
+        for (s, e) in survival.iter().zip(expected) {
+            assert!(close(*s, e, 1e-12));
+        }
+    }
+}
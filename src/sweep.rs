@@ -0,0 +1,148 @@
+//!
+//! everything related to parameter sweeps over many batches
+//!
+use std::{fs::{self, File}, io::Write, path::Path};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::batch;
+use crate::cli::SimArgs;
+use crate::config::SimulationConfig;
+
+/// an experiment file: the replicates per condition and the parameters to vary
+#[derive(Debug, Deserialize)]
+pub struct Experiment {
+    ///simulations per combination of parameter values
+    #[serde(default = "default_replicates")]
+    pub replicates: u32,
+    ///config the parameters are applied to, relative to the experiment file
+    pub base: Option<String>,
+    #[serde(default, rename = "axis")]
+    pub axes: Vec<Axis>,
+}
+
+fn default_replicates() -> u32 {
+    100
+}
+
+/// one swept parameter, e.g. `meat_efficiency` or `species.Carnivore.count`
+#[derive(Debug, Deserialize)]
+pub struct Axis {
+    pub path: String,
+    pub values: Vec<Value>,
+}
+
+impl Experiment {
+    pub fn load(path: &Path) -> Result<Experiment, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read experiment {}: {}", path.display(), e))?;
+        let experiment: Experiment = toml::from_str(&text)
+            .map_err(|e| format!("invalid experiment {}: {}", path.display(), e))?;
+        if experiment.replicates < 1 {
+            return Err(String::from("an experiment needs at least 1 replicate"));
+        }
+        if let Some(axis) = experiment.axes.iter().find(|a| a.values.is_empty()) {
+            return Err(format!("axis {} has no values", axis.path));
+        }
+        Ok(experiment)
+    }
+
+    ///every combination of axis values, the last axis changing fastest
+    pub fn conditions(&self) -> Vec<Vec<&Value>> {
+        let mut conditions: Vec<Vec<&Value>> = vec![vec![]];
+        for axis in &self.axes {
+            conditions = conditions.into_iter()
+                .flat_map(|c| axis.values.iter().map(move |v| {
+                    let mut next = c.clone();
+                    next.push(v);
+                    next
+                }))
+                .collect();
+        }
+        conditions
+    }
+}
+
+/// runs every condition of the experiment as a batch in its own folder and
+/// writes one row per condition to sweep.csv in the output folder.
+/// all conditions use the same seeds, so they differ only by their parameters
+pub fn run_sweep(path: &Path, args: &SimArgs, config: SimulationConfig, seed: u64, threads: Option<usize>) {
+    let experiment = Experiment::load(path).expect("experiment problem");
+    let base = match &experiment.base {
+        Some(base) => SimulationConfig::load(&path.parent().unwrap_or(Path::new(".")).join(base)).expect("config problem"),
+        None => config,
+    };
+    let names: Vec<String> = base.species.iter().map(|s| s.name.clone()).collect();
+    let mut table = File::create(args.output.join("sweep.csv")).expect("file problem");
+    let mut header = vec![String::from("condition")];
+    header.extend(experiment.axes.iter().map(|a| a.path.clone()));
+    header.extend([String::from("runs"), String::from("completed")]);
+    for name in &names {
+        header.extend([format!("{}_died_out", name), format!("{}_die_out_rate", name), format!("{}_average", name)]);
+    }
+    table.write_all(format!("{}\n", header.join(",")).as_bytes()).expect("sweep file fail!");
+
+    for (c, values) in experiment.conditions().into_iter().enumerate() {
+        let config = apply(&base, &experiment.axes, &values).unwrap_or_else(|e| panic!("condition {}: {}", c, e));
+        let mut cond_args = args.clone();
+        cond_args.output = args.output.join(format!("c{}", c));
+        fs::create_dir_all(&cond_args.output).expect("could not create output folder");
+        let settings: Vec<String> = experiment.axes.iter().zip(&values).map(|(a, v)| format!("{} = {}", a.path, v)).collect();
+        println!("condition {}: {}", c, settings.join(", "));
+
+        let results = batch::run_batch(&cond_args, &config, seed, experiment.replicates, threads);
+        let runs = results.len().max(1) as f64;
+        let mut row = vec![c.to_string()];
+        row.extend(values.iter().map(|v| csv_value(v)));
+        row.push(results.len().to_string());
+        row.push(results.iter().filter(|r| r.epoch == args.epochs).count().to_string());
+        for i in 0..names.len() {
+            let died_out = results.iter().filter(|r| r.get_extinction(i).is_some()).count();
+            let average: i32 = results.iter().map(|r| r.get_average(i)).sum();
+            row.extend([died_out.to_string(), format!("{}", died_out as f64 / runs), format!("{}", average as f64 / runs)]);
+        }
+        table.write_all(format!("{}\n", row.join(",")).as_bytes()).expect("sweep file fail!");
+    }
+}
+
+/// the base config with the value of every axis written to its path
+fn apply(base: &SimulationConfig, axes: &[Axis], values: &[&Value]) -> Result<SimulationConfig, String> {
+    let mut tree = serde_json::to_value(base).map_err(|e| e.to_string())?;
+    for (axis, value) in axes.iter().zip(values) {
+        set_path(&mut tree, &axis.path, (*value).clone())?;
+    }
+    let config: SimulationConfig = serde_json::from_value(tree).map_err(|e| e.to_string())?;
+    config.validate()?;
+    Ok(config)
+}
+
+/// sets a dotted path like `world.width`, list entries are picked by their name or index
+fn set_path(tree: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    let (head, rest) = match path.split_once('.') {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (path, None),
+    };
+    let child = match tree {
+        Value::Object(map) => map.get_mut(head),
+        Value::Array(list) => match head.parse::<usize>() {
+            Ok(i) => list.get_mut(i),
+            Err(_) => list.iter_mut().find(|e| e.get("name").and_then(Value::as_str) == Some(head)),
+        },
+        _ => None,
+    }.ok_or_else(|| format!("unknown parameter {}", head))?;
+    match rest {
+        Some(rest) => set_path(child, rest, value),
+        None => {
+            *child = value;
+            Ok(())
+        }
+    }
+}
+
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
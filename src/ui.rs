@@ -1,24 +1,169 @@
+//!
+//! everything related to watching a simulation in the terminal
+//!
+use std::{collections::HashMap, io, time::{Duration, Instant}};
 
-use std::io;
-///
-
-pub fn ask_for_file_name() -> String {
-    println!("input filename for evaluation");
-    let mut buffer = String::new();
-    let buffer = loop {
-        io::stdin().read_line(&mut buffer).expect("readline failed in 'ask_for_file_name'");
-        if buffer.trim().contains(".") {
-            println!("invalid input: only file name needed.");
-            continue;
-        }
-        break buffer.trim().to_owned();
-    };
-    buffer
-}
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
 
+use crate::genome::Genome;
+use crate::simulation::{letter, BasicSimulation};
 
+/// colors of the species in the order of the config, repeated if there are more species
+const COLORS: [Color; 6] = [Color::Yellow, Color::Red, Color::Cyan, Color::Magenta, Color::Blue, Color::White];
+const PLANT_COLOR: Color = Color::Green;
+/// cells per character at the zoom levels
+const ZOOMS: [i32; 4] = [1, 2, 4, 8];
 
+/// what the viewer shows and how fast the simulation runs
+struct View {
+    names: Vec<String>,
+    cursor: (i32, i32),
+    zoom: usize,
+    paused: bool,
+    steps_per_second: f32,
+    ///population of every species after each step
+    history: Vec<Vec<u64>>,
+}
 
+/// runs `sim` in a terminal ui until it is finished and the user quits.
+/// space pauses, n steps while paused, < and > change the speed, + and - zoom,
+/// the arrow keys or hjkl move the cursor, q quits
+pub fn watch<G: Genome>(sim: &mut BasicSimulation<G>) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, sim);
+    ratatui::restore();
+    result
+}
 
+fn event_loop<G: Genome>(terminal: &mut DefaultTerminal, sim: &mut BasicSimulation<G>) -> io::Result<()> {
+    let names = sim.species_names();
+    let mut view = View {
+        history: sim.counts().into_iter().map(|c| vec![c as u64]).collect(),
+        names,
+        cursor: (0, 0),
+        zoom: 0,
+        paused: true,
+        steps_per_second: 2.0,
+    };
+    let mut last_step = Instant::now();
+    loop {
+        terminal.draw(|frame| render(frame, sim, &view))?;
+        let interval = Duration::from_secs_f32(1.0 / view.steps_per_second);
+        let timeout = if view.paused { Duration::from_millis(250) } else { interval.saturating_sub(last_step.elapsed()) };
+        let mut step = false;
+        if event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                let z = ZOOMS[view.zoom];
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char(' ') => view.paused = !view.paused,
+                    KeyCode::Char('n') if view.paused => step = true,
+                    KeyCode::Char('>') => view.steps_per_second = (view.steps_per_second * 2.0).min(1000.0),
+                    KeyCode::Char('<') => view.steps_per_second = (view.steps_per_second / 2.0).max(0.125),
+                    KeyCode::Char('+') => view.zoom = view.zoom.saturating_sub(1),
+                    KeyCode::Char('-') => view.zoom = (view.zoom + 1).min(ZOOMS.len() - 1),
+                    KeyCode::Left | KeyCode::Char('h') => view.cursor.0 -= z,
+                    KeyCode::Right | KeyCode::Char('l') => view.cursor.0 += z,
+                    KeyCode::Up | KeyCode::Char('k') => view.cursor.1 -= z,
+                    KeyCode::Down | KeyCode::Char('j') => view.cursor.1 += z,
+                    _ => {}
+                }
+            }
+        } else if !view.paused {
+            step = true;
+        }
+        if step && sim.advance() {
+            last_step = Instant::now();
+            for (h, c) in view.history.iter_mut().zip(sim.counts()) {
+                h.push(c as u64);
+            }
+        }
+    }
+}
 
+fn render<G: Genome>(frame: &mut Frame, sim: &BasicSimulation<G>, view: &View) {
+    let [map_area, side] = Layout::horizontal([Constraint::Min(20), Constraint::Length(44)]).areas(frame.area());
+    let spark_height = 3 * view.names.len() as u16;
+    let [info_area, spark_area] = Layout::vertical([Constraint::Min(8), Constraint::Length(spark_height)]).areas(side);
+    render_map(frame, map_area, sim, view);
+    render_info(frame, info_area, sim, view);
+    let areas = Layout::vertical(vec![Constraint::Length(3); view.names.len()]).split(spark_area);
+    for (i, name) in view.names.iter().enumerate() {
+        let data = &view.history[i];
+        let width = areas[i].width.saturating_sub(2) as usize;
+        let count = data.last().copied().unwrap_or(0);
+        let sparkline = Sparkline::default()
+            .block(Block::bordered().title(format!("{}: {}", name, count)))
+            .data(&data[data.len().saturating_sub(width)..])
+            .style(Style::default().fg(COLORS[i % COLORS.len()]));
+        frame.render_widget(sparkline, areas[i]);
+    }
+}
 
+/// the map around the cursor, one character shows zoom x zoom cells.
+/// later species are drawn on top of earlier ones and of plants
+fn render_map<G: Genome>(frame: &mut Frame, area: Rect, sim: &BasicSimulation<G>, view: &View) {
+    let z = ZOOMS[view.zoom];
+    let (w, h) = (area.width.saturating_sub(2) as i32, area.height.saturating_sub(2) as i32);
+    let origin = (view.cursor.0 - w / 2 * z, view.cursor.1 - h / 2 * z);
+    let block_of = |pos: &(i32, i32)| ((pos.0 - origin.0).div_euclid(z), (pos.1 - origin.1).div_euclid(z));
+    //the highest layer found in every block, 0 for plants and i + 1 for species i
+    let mut layers: HashMap<(i32, i32), usize> = HashMap::new();
+    for pos in sim.plant_positions() {
+        layers.entry(block_of(pos)).or_insert(0);
+    }
+    for i in 0..view.names.len() {
+        for pos in sim.species_positions(i) {
+            let layer = layers.entry(block_of(pos)).or_insert(i + 1);
+            *layer = (*layer).max(i + 1);
+        }
+    }
+    let world = sim.get_world();
+    let cursor = block_of(&view.cursor);
+    let lines: Vec<Line> = (0..h).map(|y| {
+        Line::from((0..w).map(|x| {
+            let (text, style) = match layers.get(&(x, y)) {
+                Some(0) => (String::from("*"), Style::default().fg(PLANT_COLOR)),
+                Some(l) => (letter(&view.names[l - 1]).to_string(), Style::default().fg(COLORS[(l - 1) % COLORS.len()])),
+                None if world.cell((origin.0 + x * z, origin.1 + y * z)).is_none() => (String::from(" "), Style::default()),
+                None => (String::from("."), Style::default().fg(Color::DarkGray)),
+            };
+            let style = if (x, y) == cursor { style.add_modifier(Modifier::REVERSED) } else { style };
+            Span::styled(text, style)
+        }).collect::<Vec<Span>>())
+    }).collect();
+    let title = format!("world {}x{} {:?}, zoom 1:{}", world.width, world.height, world.topology, z);
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), area);
+}
+
+/// the state of the run and every creature under the cursor
+fn render_info<G: Genome>(frame: &mut Frame, area: Rect, sim: &BasicSimulation<G>, view: &View) {
+    let (epoch, step) = sim.progress();
+    let state = if sim.is_finished() { "finished" } else if view.paused { "paused" } else { "running" };
+    let mut lines = vec![
+        Line::from(format!("epoch {}/{} step {}/{}", epoch, sim.get_epochs(), step, sim.get_sim_time())),
+        Line::from(format!("{} at {} steps/s", state, view.steps_per_second)),
+        Line::from("space pause, n step, < > speed"),
+        Line::from("+ - zoom, arrows/hjkl move, q quit"),
+        Line::from(""),
+        Line::from(format!("cell ({}, {}){}", view.cursor.0, view.cursor.1, if sim.plant_at(&view.cursor) { " with plant" } else { "" })),
+    ];
+    for (i, creature) in sim.creatures_at(&view.cursor) {
+        lines.push(Line::from(Span::styled(
+            format!("{} #{} energy {:.2}", view.names[i], creature.id, creature.get_energy()),
+            Style::default().fg(COLORS[i % COLORS.len()]),
+        )));
+        for (name, value) in sim.species_gene_names()[i].iter().zip(creature.genes()) {
+            lines.push(Line::from(format!("  {}: {}", name, value)));
+        }
+    }
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title("simulation")), area);
+}
@@ -0,0 +1,166 @@
+//!
+//! everything related to the space the creatures live in
+//!
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// what happens at the border of the world
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Topology {
+    ///the border can't be crossed
+    #[default]
+    Walls,
+    ///leaving on one side enters on the opposite side
+    Torus,
+    ///creatures spawn inside the bounds but can walk anywhere
+    Unbounded,
+}
+
+/// the shape of what a creature can see, measured from the creature itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    ///|dx| + |dy| <= r, a diamond
+    #[default]
+    Manhattan,
+    ///dx² + dy² <= r², a circle
+    Euclidean,
+    ///max(|dx|, |dy|) <= r, a square
+    Chebyshev,
+}
+
+impl Metric {
+    ///whether the offset (dx, dy) lies within distance r
+    pub fn contains(&self, dx: i64, dy: i64, r: i64) -> bool {
+        match self {
+            Metric::Manhattan => dx.abs() + dy.abs() <= r,
+            Metric::Euclidean => dx.saturating_mul(dx).saturating_add(dy.saturating_mul(dy)) <= r.saturating_mul(r),
+            Metric::Chebyshev => dx.abs().max(dy.abs()) <= r,
+        }
+    }
+}
+
+/// min, max and shift of a rectangle part, see [`World::visible_parts`]
+pub type Part = ((i32, i32), (i32, i32), (i32, i32));
+
+/// a grid of `width` x `height` cells centered around (0,0)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct World {
+    pub width: i32,
+    pub height: i32,
+    pub topology: Topology,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        World { width: 101, height: 101, topology: Topology::Walls }
+    }
+}
+
+impl World {
+    ///the top left cell
+    pub fn min(&self) -> (i32, i32) {
+        (-(self.width / 2), -(self.height / 2))
+    }
+
+    ///the bottom right cell
+    pub fn max(&self) -> (i32, i32) {
+        let min = self.min();
+        (min.0 + self.width - 1, min.1 + self.height - 1)
+    }
+
+    pub fn contains(&self, pos: &(i32, i32)) -> bool {
+        let (min, max) = (self.min(), self.max());
+        min.0 <= pos.0 && pos.0 <= max.0 && min.1 <= pos.1 && pos.1 <= max.1
+    }
+
+    ///a random cell inside the bounds
+    pub fn random_pos<R: Rng>(&self, rng: &mut R) -> (i32, i32) {
+        let (min, max) = (self.min(), self.max());
+        (rng.gen_range(min.0..=max.0), rng.gen_range(min.1..=max.1))
+    }
+
+    ///the cell reached by going `dir` from `pos`, walls keep the creature in place
+    pub fn step(&self, pos: (i32, i32), dir: (i32, i32)) -> (i32, i32) {
+        let next = (pos.0.wrapping_add(dir.0), pos.1.wrapping_add(dir.1));
+        match self.topology {
+            Topology::Walls if !self.contains(&next) => pos,
+            _ => self.cell(next).unwrap_or(pos),
+        }
+    }
+
+    ///the cell a possibly out of bounds coordinate refers to, None if it is behind a wall
+    pub fn cell(&self, pos: (i32, i32)) -> Option<(i32, i32)> {
+        match self.topology {
+            Topology::Walls => self.contains(&pos).then_some(pos),
+            Topology::Torus => {
+                let min = self.min();
+                Some((
+                    min.0 + (pos.0 - min.0).rem_euclid(self.width),
+                    min.1 + (pos.1 - min.1).rem_euclid(self.height),
+                ))
+            }
+            Topology::Unbounded => Some(pos),
+        }
+    }
+
+    ///the parts of the rectangle `min`..=`max` that lie in the world, each with the shift
+    ///that turns a cell of the part back into the coordinates of the rectangle.
+    ///only a torus shifts, its rectangle may reach across the border
+    pub fn visible_parts(&self, min: (i32, i32), max: (i32, i32)) -> Vec<Part> {
+        match self.topology {
+            Topology::Unbounded => vec![(min, max, (0, 0))],
+            Topology::Walls => self.clip(min, max).map(|(a, b)| vec![(a, b, (0, 0))]).unwrap_or_default(),
+            Topology::Torus => {
+                let mut parts = vec![];
+                for sx in [-self.width, 0, self.width] {
+                    for sy in [-self.height, 0, self.height] {
+                        let shifted = ((min.0.saturating_add(sx), min.1.saturating_add(sy)), (max.0.saturating_add(sx), max.1.saturating_add(sy)));
+                        if let Some((a, b)) = self.clip(shifted.0, shifted.1) {
+                            parts.push((a, b, (-sx, -sy)));
+                        }
+                    }
+                }
+                parts
+            }
+        }
+    }
+
+    fn clip(&self, min: (i32, i32), max: (i32, i32)) -> Option<((i32, i32), (i32, i32))> {
+        let (wmin, wmax) = (self.min(), self.max());
+        let a = (min.0.max(wmin.0), min.1.max(wmin.1));
+        let b = (max.0.min(wmax.0), max.1.min(wmax.1));
+        (a.0 <= b.0 && a.1 <= b.1).then_some((a, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_are_symmetric() {
+        for metric in [Metric::Manhattan, Metric::Euclidean, Metric::Chebyshev] {
+            for r in 0..=10 {
+                for dx in -12..=12 {
+                    for dy in -12..=12 {
+                        let inside = metric.contains(dx, dy, r);
+                        assert_eq!(inside, metric.contains(-dx, dy, r), "{:?} ({}, {}) r {}", metric, dx, dy, r);
+                        assert_eq!(inside, metric.contains(dx, -dy, r), "{:?} ({}, {}) r {}", metric, dx, dy, r);
+                        assert_eq!(inside, metric.contains(dy, dx, r), "{:?} ({}, {}) r {}", metric, dx, dy, r);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn metrics_have_their_shape() {
+        assert!(Metric::Manhattan.contains(2, 1, 3) && !Metric::Manhattan.contains(2, 2, 3));
+        assert!(Metric::Euclidean.contains(3, 4, 5) && !Metric::Euclidean.contains(4, 4, 5));
+        assert!(Metric::Chebyshev.contains(3, 3, 3) && !Metric::Chebyshev.contains(4, 0, 3));
+        assert!(Metric::Euclidean.contains(0, 0, 0) && !Metric::Euclidean.contains(i64::MAX, 0, 5));
+    }
+}